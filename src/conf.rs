@@ -1,4 +1,10 @@
-use chrono::Duration;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
 
 pub static FORMAT_MINUTE_PRECISION_DATETIME: &str = "%F %R";
 pub static FORMAT_SECOND_PRECISION_DATETIME: &str = "%F %T";
@@ -14,10 +20,418 @@ pub static FORMAT_TIME: &str = "%R";
 pub static FORMAT_TIME: &str = "%T";
 
 pub static FORMAT_DATE: &str = "%F";
+pub static FORMAT_EXPORT_DATETIME: &str = "%FT%T";
 pub static DEFAULT_WIDTH: usize = usize::MAX;
 pub static REPORT_INDENTATION: usize = 4;
+pub static MAX_JOURNAL_ENTRIES: usize = 20;
+pub static DEFAULT_BACKUPS: usize = 3;
+pub static LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+pub static CONFIG_FILE_ENV: &str = "BARTIB_CONFIG";
+pub static REPORT_RANGES: [&str; 7] = [
+    "today",
+    "yesterday",
+    "current_week",
+    "last_week",
+    "current_month",
+    "last_month",
+    "current_year",
+];
+pub static ROUND_STRATEGIES: [&str; 3] = ["nearest", "up", "down"];
+pub static ROUND_MODES: [&str; 3] = ["timestamps", "durations", "totals"];
+pub static ZONES: [&str; 2] = ["original", "current"];
 
 #[derive(Debug)]
 pub struct ProcessConfig {
     pub round: Option<Duration>,
 }
+
+// the settings of the configuration file
+//
+// every setting is optional and only serves as default for the corresponding command line
+// option or environment variable
+#[derive(Debug, Default)]
+pub struct Config {
+    pub file: Option<String>,
+    pub editor: Option<String>,
+    pub backups: Option<usize>,
+    pub round: Option<String>,
+    pub round_strategy: Option<String>,
+    pub round_mode: Option<String>,
+    pub min_duration: Option<String>,
+    pub week_start: Option<Weekday>,
+    pub zone: Option<String>,
+    pub report_range: Option<String>,
+    pub color: Option<bool>,
+    pub currency: Option<String>,
+    pub rates: Vec<HourlyRate>,
+    pub working_time: Option<WorkingTime>,
+}
+
+// the hourly rate for all projects matching a pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct HourlyRate {
+    // the name of the project. Supports '?' and '*' as wildcards
+    pub project: String,
+    pub rate: f64,
+}
+
+// the working time model by which target hours and the overtime balance are calculated
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkingTime {
+    // the target hours of each day of the week, starting on monday
+    pub hours_per_weekday: [Duration; 7],
+    // the first day from which on the balance is calculated
+    pub start: Option<NaiveDate>,
+    // days without target hours
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl WorkingTime {
+    // the target hours of a day
+    #[must_use]
+    pub fn get_hours(&self, date: NaiveDate) -> Duration {
+        if self.holidays.contains(&date) {
+            Duration::zero()
+        } else {
+            self.hours_per_weekday[date.weekday().num_days_from_monday() as usize]
+        }
+    }
+}
+
+impl Config {
+    // loads the configuration file
+    //
+    // the location of the file may be set via BARTIB_CONFIG. Otherwise the file is expected in
+    // the XDG config directory. A missing file results in an empty configuration.
+    pub fn load() -> Result<Self> {
+        let (path, is_explicit) = match env::var_os(CONFIG_FILE_ENV) {
+            Some(path) => (PathBuf::from(path), true),
+            None => match get_default_config_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::from_toml(&content).context(format!(
+                "Could not read the configuration file {}",
+                path.display()
+            )),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !is_explicit => Ok(Self::default()),
+            Err(e) => Err(anyhow!(e)).context(format!(
+                "Could not read the configuration file {}",
+                path.display()
+            )),
+        }
+    }
+
+    // parses the content of a configuration file
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let table: toml::Table = content.parse()?;
+        let mut config = Self::default();
+
+        for (key, value) in &table {
+            match key.as_str() {
+                "file" => config.file = Some(expand_home(get_string(key, value)?)),
+                "editor" => config.editor = Some(get_string(key, value)?.to_string()),
+                "backups" => {
+                    config.backups = Some(
+                        value
+                            .as_integer()
+                            .and_then(|backups| usize::try_from(backups).ok())
+                            .context(format!("The setting {key} must be a positive number"))?,
+                    );
+                }
+                "round" => config.round = Some(get_string(key, value)?.to_string()),
+                "round_strategy" => {
+                    config.round_strategy = Some(get_choice(key, value, &ROUND_STRATEGIES)?);
+                }
+                "round_mode" => config.round_mode = Some(get_choice(key, value, &ROUND_MODES)?),
+                "min_duration" => {
+                    config.min_duration = Some(get_string(key, value)?.to_string());
+                }
+                "week_start" => {
+                    let week_start = get_string(key, value)?;
+                    config.week_start = Some(
+                        week_start
+                            .parse()
+                            .map_err(|_| anyhow!("\"{week_start}\" is not a day of the week"))?,
+                    );
+                }
+                "zone" => config.zone = Some(get_choice(key, value, &ZONES)?),
+                "report_range" => {
+                    let report_range = get_string(key, value)?;
+                    if !REPORT_RANGES.contains(&report_range) {
+                        bail!(
+                            "\"{report_range}\" is not a valid report range. Use one of: {}",
+                            REPORT_RANGES.join(", ")
+                        );
+                    }
+                    config.report_range = Some(report_range.to_string());
+                }
+                "color" => {
+                    config.color = Some(
+                        value
+                            .as_bool()
+                            .context(format!("The setting {key} must be true or false"))?,
+                    );
+                }
+                "currency" => config.currency = Some(get_string(key, value)?.to_string()),
+                "rates" => {
+                    config.rates = value
+                        .as_array()
+                        .context("The setting rates must be a list of rates")?
+                        .iter()
+                        .map(get_hourly_rate)
+                        .collect::<Result<Vec<HourlyRate>>>()?;
+                }
+                "working_time" => config.working_time = Some(get_working_time(value)?),
+                // settings of other versions of bartib must not keep this version from running
+                _ => eprintln!("Warning: Ignoring unknown setting {key} in the configuration file"),
+            }
+        }
+
+        Ok(config)
+    }
+
+    // the first day of the week. Defaults to monday
+    #[must_use]
+    pub fn get_week_start(&self) -> Weekday {
+        self.week_start.unwrap_or(Weekday::Mon)
+    }
+
+    // the number of backups kept of the activity log
+    #[must_use]
+    pub fn get_backups(&self) -> usize {
+        self.backups.unwrap_or(DEFAULT_BACKUPS)
+    }
+}
+
+fn get_string<'a>(key: &str, value: &'a toml::Value) -> Result<&'a str> {
+    value
+        .as_str()
+        .context(format!("The setting {key} must be a string"))
+}
+
+// reads a string that must be one of the given choices
+fn get_choice(key: &str, value: &toml::Value, choices: &[&str]) -> Result<String> {
+    let choice = get_string(key, value)?;
+
+    if !choices.contains(&choice) {
+        bail!(
+            "\"{choice}\" is not a valid value for {key}. Use one of: {}",
+            choices.join(", ")
+        );
+    }
+
+    Ok(choice.to_string())
+}
+
+// reads a rate like `{ project = "client-*", rate = 90 }`
+fn get_hourly_rate(value: &toml::Value) -> Result<HourlyRate> {
+    let project = value
+        .get("project")
+        .and_then(toml::Value::as_str)
+        .context("Every rate needs a project")?;
+    let rate = value
+        .get("rate")
+        .and_then(|rate| {
+            rate.as_float()
+                .or_else(|| rate.as_integer().map(|r| r as f64))
+        })
+        .context(format!("The rate for project {project} must be a number"))?;
+
+    Ok(HourlyRate {
+        project: project.to_string(),
+        rate,
+    })
+}
+
+// reads the working time model like `{ monday = 8, friday = 6.5, start = "2024-01-01" }`
+fn get_working_time(value: &toml::Value) -> Result<WorkingTime> {
+    let table = value
+        .as_table()
+        .context("The setting working_time must be a table")?;
+    let mut working_time = WorkingTime {
+        hours_per_weekday: [Duration::zero(); 7],
+        start: None,
+        holidays: Vec::new(),
+    };
+
+    for (key, value) in table {
+        match key.as_str() {
+            "start" => working_time.start = Some(get_date(key, value)?),
+            "holidays" => {
+                working_time.holidays = value
+                    .as_array()
+                    .context("The setting holidays must be a list of dates")?
+                    .iter()
+                    .map(|holiday| get_date(key, holiday))
+                    .collect::<Result<Vec<NaiveDate>>>()?;
+            }
+            _ => {
+                let Ok(weekday) = key.parse::<Weekday>() else {
+                    eprintln!(
+                        "Warning: Ignoring unknown setting working_time.{key} in the configuration file"
+                    );
+                    continue;
+                };
+                let hours = value
+                    .as_float()
+                    .or_else(|| value.as_integer().map(|h| h as f64))
+                    .context(format!("The hours for {key} must be a number"))?;
+                working_time.hours_per_weekday[weekday.num_days_from_monday() as usize] =
+                    Duration::minutes((hours * 60.0).round() as i64);
+            }
+        }
+    }
+
+    Ok(working_time)
+}
+
+// reads a date either as TOML date or as string
+fn get_date(key: &str, value: &toml::Value) -> Result<NaiveDate> {
+    let date = match value {
+        toml::Value::Datetime(datetime) => datetime.to_string(),
+        toml::Value::String(date) => date.clone(),
+        _ => bail!("The setting {key} must be a date"),
+    };
+
+    NaiveDate::parse_from_str(&date, FORMAT_DATE)
+        .context(format!("\"{date}\" is not a valid date (YYYY-MM-DD)"))
+}
+
+// $XDG_CONFIG_HOME/bartib/config.toml, ~/.config/bartib/config.toml or
+// %APPDATA%\bartib\config.toml on windows
+fn get_default_config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(config_dir.join("bartib").join("config.toml"))
+}
+
+// replaces a leading ~ with the home directory
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_from_toml() {
+        let config = Config::from_toml(
+            r#"
+            file = "/tmp/activities.bartib"
+            editor = "vim"
+            backups = 5
+            round = "15m"
+            round_strategy = "up"
+            round_mode = "durations"
+            min_duration = "15m"
+            week_start = "sunday"
+            zone = "current"
+            report_range = "current_week"
+            color = false
+            currency = "EUR"
+
+            [[rates]]
+            project = "client-*"
+            rate = 90
+
+            [[rates]]
+            project = "*"
+            rate = 60.5
+
+            [working_time]
+            monday = 8
+            friday = 6.5
+            start = 2024-01-01
+            holidays = ["2024-12-25"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.file.unwrap(), "/tmp/activities.bartib");
+        assert_eq!(config.editor.unwrap(), "vim");
+        assert_eq!(config.backups, Some(5));
+        assert_eq!(config.round.unwrap(), "15m");
+        assert_eq!(config.round_strategy.unwrap(), "up");
+        assert_eq!(config.round_mode.unwrap(), "durations");
+        assert_eq!(config.min_duration.unwrap(), "15m");
+        assert_eq!(config.week_start, Some(Weekday::Sun));
+        assert_eq!(config.zone.unwrap(), "current");
+        assert_eq!(config.report_range.unwrap(), "current_week");
+        assert_eq!(config.color, Some(false));
+        assert_eq!(config.currency.unwrap(), "EUR");
+        assert_eq!(
+            config.rates,
+            vec![
+                HourlyRate {
+                    project: "client-*".to_string(),
+                    rate: 90.0
+                },
+                HourlyRate {
+                    project: "*".to_string(),
+                    rate: 60.5
+                }
+            ]
+        );
+
+        let working_time = config.working_time.unwrap();
+        assert_eq!(working_time.hours_per_weekday[0], Duration::hours(8));
+        assert_eq!(working_time.hours_per_weekday[1], Duration::zero());
+        assert_eq!(working_time.hours_per_weekday[4], Duration::minutes(390));
+        assert_eq!(working_time.start, NaiveDate::from_ymd_opt(2024, 1, 1));
+
+        // 2024-12-25 is a wednesday, 2024-12-27 a friday
+        let christmas = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap();
+        assert_eq!(working_time.get_hours(christmas), Duration::zero());
+        assert_eq!(
+            working_time.get_hours(christmas + Duration::days(2)),
+            Duration::minutes(390)
+        );
+    }
+
+    #[test]
+    fn config_from_empty_toml() {
+        let config = Config::from_toml("").unwrap();
+
+        assert!(config.file.is_none());
+        assert_eq!(config.get_week_start(), Weekday::Mon);
+        assert_eq!(config.get_backups(), DEFAULT_BACKUPS);
+    }
+
+    #[test]
+    fn config_with_unknown_settings() {
+        let config =
+            Config::from_toml("unknown = 1\nbackups = 2\n[working_time]\nsomeday = 8\nfriday = 6")
+                .unwrap();
+        assert_eq!(config.get_backups(), 2);
+        assert_eq!(
+            config.working_time.unwrap().hours_per_weekday[4],
+            Duration::hours(6)
+        );
+    }
+
+    #[test]
+    fn config_from_invalid_toml() {
+        assert!(Config::from_toml("week_start = \"someday\"").is_err());
+        assert!(Config::from_toml("report_range = \"forever\"").is_err());
+        assert!(Config::from_toml("color = \"yes\"").is_err());
+        assert!(Config::from_toml("backups = -1").is_err());
+        assert!(Config::from_toml("round_strategy = \"sideways\"").is_err());
+        assert!(Config::from_toml("round_mode = \"everything\"").is_err());
+        assert!(Config::from_toml("zone = \"utc\"").is_err());
+        assert!(Config::from_toml("file = ").is_err());
+        assert!(Config::from_toml("[working_time]\nstart = \"2024-13-01\"").is_err());
+        assert!(Config::from_toml("[[rates]]\nproject = \"p\"\nrate = \"a lot\"").is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::bartib_file;
+use crate::data::getter;
+use crate::data::processor;
+use crate::data::working_time;
+use crate::view::balance;
+
+// shows the target and the actual working time of each week and the resulting overtime
+//
+// the balance starts at the start of the working time model unless another start is given
+pub fn show_balance(
+    file_name: &str,
+    filter: getter::ActivityFilter,
+    processors: processor::ProcessorList,
+    config: &conf::Config,
+) -> Result<()> {
+    let working_time = config.working_time.as_ref().context(
+        "Please configure your working time in the configuration file to calculate a balance",
+    )?;
+    let from = filter.from_date.or(working_time.start).context(
+        "Please specify the start of the balance either with --from or as start of the working time in the configuration file",
+    )?;
+    let to = filter
+        .to_date
+        .unwrap_or_else(|| Local::now().naive_local().date());
+
+    let file_content = bartib_file::get_file_content(file_name)?;
+    let activities = getter::get_activities(&file_content).collect();
+
+    let processed_activities_bind: Vec<activity::Activity> =
+        processor::process_activities(activities, processors);
+    let processed_activities: Vec<&activity::Activity> = processed_activities_bind.iter().collect();
+
+    let filtered_activities: Vec<&activity::Activity> =
+        getter::filter_activities(processed_activities, &filter);
+
+    let periods = working_time::get_weekly_periods(
+        working_time,
+        &filtered_activities,
+        from,
+        to,
+        config.get_week_start(),
+    );
+
+    balance::show_balance(&periods);
+
+    Ok(())
+}
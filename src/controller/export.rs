@@ -0,0 +1,49 @@
+use anyhow::{Context, Result};
+
+use crate::data::activity;
+use crate::data::bartib_file;
+use crate::data::getter;
+use crate::data::processor;
+use crate::view::export;
+
+// exports tracked activities in a machine readable format
+//
+// the activities will be ordered chronologically.
+pub fn export(
+    file_name: &str,
+    filter: getter::ActivityFilter,
+    processors: processor::ProcessorList,
+    format: &export::ExportFormat,
+) -> Result<()> {
+    let file_content = bartib_file::get_file_content(file_name)?;
+    let line_numbers: Vec<Option<usize>> = file_content
+        .iter()
+        .filter(|line| line.activity.is_ok())
+        .map(|line| line.line_number)
+        .collect();
+    let activities = line_numbers
+        .into_iter()
+        .zip(getter::get_activities(&file_content))
+        .collect();
+
+    let processed_activities_bind: Vec<(Option<usize>, activity::Activity)> =
+        processor::process_indexed_activities(activities, processors);
+
+    let mut filtered_activities: Vec<(Option<usize>, &activity::Activity)> =
+        processed_activities_bind
+            .iter()
+            .map(|(line_number, activity)| (*line_number, activity))
+            .filter(|(_, activity)| filter.matches(activity))
+            .collect();
+
+    filtered_activities.sort_by_key(|(_, activity)| activity.start);
+
+    let first_element = filtered_activities.len().saturating_sub(
+        filter
+            .number_of_activities
+            .unwrap_or(filtered_activities.len()),
+    );
+
+    export::export_activities(&filtered_activities[first_element..], format)
+        .context("Could not export activities")
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::io::Read;
+
+use crate::controller::manipulation;
+use crate::data::activity;
+use crate::data::bartib_file;
+use crate::data::getter;
+use crate::data::import;
+use crate::view::list;
+
+// imports activities from the file of another time tracker
+//
+// activities that are already part of the bartib file are skipped. With `dry_run` the
+// activities that would be imported are only displayed.
+pub fn import(
+    file_name: &str,
+    input_file_name: &str,
+    format: &import::ImportFormat,
+    dry_run: bool,
+) -> Result<()> {
+    let input = read_input(input_file_name)?;
+    let mut imported_activities = import::parse_activities(&input, format).context(format!(
+        "Could not import activities from {input_file_name}"
+    ))?;
+    imported_activities.sort_by_key(|activity| activity.start);
+
+    let _lock = manipulation::lock_for_changes(file_name)?;
+
+    // the bartib file may not exist yet
+    let mut file_content = bartib_file::get_file_content(file_name).unwrap_or_default();
+
+    // activities are compared as they would be written to the file, as timestamps are
+    // truncated to the precision of the file
+    let mut known_activities: HashSet<String> = getter::get_activities(&file_content)
+        .map(|activity| activity.to_string())
+        .collect();
+
+    let number_of_imported_activities = imported_activities.len();
+    let new_activities: Vec<activity::Activity> = imported_activities
+        .into_iter()
+        .filter(|activity| known_activities.insert(activity.to_string()))
+        .collect();
+    let number_of_duplicates = number_of_imported_activities - new_activities.len();
+
+    if dry_run {
+        // imported activities are appended to the file
+        let activities_with_indexes: Vec<(Option<usize>, &activity::Activity)> = new_activities
+            .iter()
+            .enumerate()
+            .map(|(i, activity)| (Some(file_content.len() + i + 1), activity))
+            .collect();
+        list::list_activities(&activities_with_indexes, true, false);
+        println!(
+            "\n{} activities would be imported, {} duplicates would be skipped",
+            new_activities.len(),
+            number_of_duplicates
+        );
+        return Ok(());
+    }
+
+    let number_of_new_activities = new_activities.len();
+
+    if number_of_new_activities > 0 {
+        file_content.extend(
+            new_activities
+                .into_iter()
+                .map(bartib_file::Line::for_activity),
+        );
+        bartib_file::write_to_file(file_name, &file_content)
+            .context(format!("Could not write to file: {file_name}"))?;
+    }
+
+    println!(
+        "Imported {number_of_new_activities} activities, skipped {number_of_duplicates} duplicates"
+    );
+
+    Ok(())
+}
+
+// reads the file to import. "-" reads from stdin
+fn read_input(input_file_name: &str) -> Result<String> {
+    if input_file_name == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .context("Could not read from stdin")?;
+        Ok(input)
+    } else {
+        fs::read_to_string(input_file_name)
+            .context(format!("Could not read from file: {input_file_name}"))
+    }
+}
@@ -1,5 +1,4 @@
 use anyhow::Result;
-use chrono::NaiveDateTime;
 use wildmatch::WildMatch;
 
 use crate::conf;
@@ -9,13 +8,23 @@ use crate::data::bartib_file;
 use crate::data::getter;
 use crate::data::processor;
 use crate::view::list;
+use crate::view::template::Template;
 
 // lists all currently running activities.
-pub fn list_running(file_name: &str) -> Result<()> {
+pub fn list_running(file_name: &str, template: Option<&Template>) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
     let running_activities = getter::get_running_activities(&file_content);
 
-    list::list_running_activities(&running_activities);
+    match template {
+        Some(template) => list::list_activities_with_template(
+            &running_activities
+                .into_iter()
+                .map(|activity| (None, activity))
+                .collect::<Vec<_>>(),
+            template,
+        ),
+        None => list::list_running_activities(&running_activities),
+    }
 
     Ok(())
 }
@@ -27,18 +36,33 @@ pub fn list(
     file_name: &str,
     filter: getter::ActivityFilter,
     do_group_activities: bool,
+    with_notes: bool,
     processors: processor::ProcessorList,
+    template: Option<&Template>,
 ) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
-    let activities = getter::get_activities(&file_content).collect();
-    let processed_activities_bind: Vec<activity::Activity> =
-        processor::process_activities(activities, processors);
-    let processed_activities: Vec<&activity::Activity> = processed_activities_bind.iter().collect();
+    let line_numbers: Vec<Option<usize>> = file_content
+        .iter()
+        .filter(|line| line.activity.is_ok())
+        .map(|line| line.line_number)
+        .collect();
 
-    let mut filtered_activities: Vec<&activity::Activity> =
-        getter::filter_activities(processed_activities, &filter);
+    // the line numbers are shown as indexes of the activities
+    let activities = line_numbers
+        .into_iter()
+        .zip(getter::get_activities(&file_content))
+        .collect();
+    let processed_activities_bind: Vec<(Option<usize>, activity::Activity)> =
+        processor::process_indexed_activities(activities, processors);
 
-    filtered_activities.sort_by_key(|activity| activity.start);
+    let mut filtered_activities: Vec<(Option<usize>, &activity::Activity)> =
+        processed_activities_bind
+            .iter()
+            .map(|(line_number, activity)| (*line_number, activity))
+            .filter(|(_, activity)| filter.matches(activity))
+            .collect();
+
+    filtered_activities.sort_by_key(|(_, activity)| activity.start);
 
     let first_element = filtered_activities.len().saturating_sub(
         filter
@@ -46,11 +70,17 @@ pub fn list(
             .unwrap_or(filtered_activities.len()),
     );
 
-    if do_group_activities {
-        list::list_activities_grouped_by_date(&filtered_activities[first_element..]);
+    if let Some(template) = template {
+        list::list_activities_with_template(&filtered_activities[first_element..], template);
+    } else if do_group_activities {
+        list::list_activities_grouped_by_date(&filtered_activities[first_element..], with_notes);
     } else {
         let with_start_dates = filter.date.is_none();
-        list::list_activities(&filtered_activities[first_element..], with_start_dates);
+        list::list_activities(
+            &filtered_activities[first_element..],
+            with_start_dates,
+            with_notes,
+        );
     }
 
     Ok(())
@@ -59,9 +89,9 @@ pub fn list(
 // checks the file content for sanity
 pub fn sanity_check(file_name: &str) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
-    let mut lines_with_activities: Vec<(Option<usize>, Activity)> = file_content
-        .into_iter()
-        .filter_map(|line| match line.activity {
+    let mut lines_with_activities: Vec<(Option<usize>, &Activity)> = file_content
+        .iter()
+        .filter_map(|line| match &line.activity {
             Ok(a) => Some((line.line_number, a)),
             Err(_) => None,
         })
@@ -69,19 +99,14 @@ pub fn sanity_check(file_name: &str) -> Result<()> {
     lines_with_activities.sort_unstable_by_key(|(_, activity)| activity.start);
 
     let mut has_finding: bool = false;
-    let mut last_end: Option<NaiveDateTime> = None;
+    // the activity which ends last of all activities checked so far
+    let mut last_ending: Option<&Activity> = None;
 
-    for (line_number, activity) in lines_with_activities {
-        has_finding = !check_sanity(last_end, &activity, line_number) || has_finding;
+    for (line_number, activity) in &lines_with_activities {
+        has_finding = !check_sanity(last_ending, activity, *line_number) || has_finding;
 
-        if let Some(e) = last_end {
-            if let Some(this_end) = activity.end {
-                if this_end > e {
-                    last_end = Some(this_end);
-                }
-            }
-        } else {
-            last_end = activity.end;
+        if last_ending.is_none_or(|last| activity.get_end_or_now() > last.get_end_or_now()) {
+            last_ending = Some(activity);
         }
     }
 
@@ -93,7 +118,7 @@ pub fn sanity_check(file_name: &str) -> Result<()> {
 }
 
 fn check_sanity(
-    last_end: Option<NaiveDateTime>,
+    last_ending: Option<&Activity>,
     activity: &Activity,
     line_number: Option<usize>,
 ) -> bool {
@@ -103,8 +128,8 @@ fn check_sanity(
         sane = false;
     }
 
-    if let Some(e) = last_end {
-        if e > activity.start {
+    if let Some(last) = last_ending {
+        if last.overlaps(activity) {
             println!("Activity started before another activity ended");
             sane = false;
         }
@@ -134,10 +159,7 @@ fn print_activity_with_line(activity: &Activity, line_number: usize) {
 pub fn check(file_name: &str) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
 
-    let number_of_errors = file_content
-        .iter()
-        .filter(|line| line.activity.is_err())
-        .count();
+    let number_of_errors = file_content.iter().filter(|line| line.has_error()).count();
 
     if number_of_errors == 0 {
         println!("All lines in the file have been successfully parsed as activities.");
@@ -148,7 +170,7 @@ pub fn check(file_name: &str) -> Result<()> {
 
     file_content
         .iter()
-        .filter(|line| line.activity.is_err() && line.plaintext.is_some())
+        .filter(|line| line.has_error() && line.plaintext.is_some())
         .for_each(|line| {
             if let Err(e) = &line.activity {
                 println!(
@@ -186,6 +208,26 @@ pub fn list_projects(file_name: &str, current: bool, no_quotes: bool) -> Result<
     Ok(())
 }
 
+// lists the backups of the activity log, starting with the latest one
+pub fn list_backups(file_name: &str) -> Result<()> {
+    let backups = bartib_file::get_backups(file_name);
+
+    if backups.is_empty() {
+        println!("There are no backups of {file_name}");
+    }
+
+    for backup in backups {
+        let time = backup.time.map_or("unknown".to_string(), |time| {
+            time.format(conf::FORMAT_DATETIME).to_string()
+        });
+        let lines = bartib_file::read_lines(&backup.path.to_string_lossy())?;
+
+        println!("{:>3}  {time}  {} lines", backup.number, lines.len());
+    }
+
+    Ok(())
+}
+
 // return last finished activity
 pub fn list_last_activities(file_name: &str, number: usize) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
@@ -1,11 +1,13 @@
 use anyhow::{anyhow, bail, Context, Error, Result};
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime};
 use std::process::Command;
+use std::str::FromStr;
 
 use crate::conf;
 use crate::data::activity;
 use crate::data::bartib_file;
 use crate::data::getter;
+use crate::data::journal;
 use crate::view::format_util;
 
 // starts a new activity
@@ -13,38 +15,101 @@ pub fn start(
     file_name: &str,
     project_name: &str,
     activity_description: &str,
+    tags: &[String],
+    notes: Option<&str>,
     time: Option<NaiveDateTime>,
 ) -> Result<()> {
-    let mut file_content: Vec<bartib_file::Line> = Vec::new();
+    let _lock = lock_for_changes(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name).unwrap_or_default();
 
-    if let Ok(mut previous_file_content) = bartib_file::get_file_content(file_name) {
-        // if we start a new activities programaticly, we stop all other activities first.
-        // However, we must not assume that there is always only one activity
-        // running as the user may have started activities manually
-        stop_all_running_activities(&mut previous_file_content, time);
-
-        file_content.append(&mut previous_file_content);
-    }
+    // if we start a new activities programaticly, we stop all other activities first.
+    // However, we must not assume that there is always only one activity
+    // running as the user may have started activities manually
+    stop_all_running_activities(&mut file_content, time);
 
-    let activity = activity::Activity::start(
+    let mut activity = activity::Activity::start(
         project_name.to_string(),
         activity_description.to_string(),
         time,
     );
+    activity.tags = tags.to_vec();
+    if let Some(notes) = notes {
+        activity.set_notes(notes);
+    }
 
     save_new_activity(file_name, &mut file_content, activity)
 }
 
+// the end of an activity that is added, either as time or as duration after its start
+pub enum ActivityEnd {
+    Time(NaiveDateTime),
+    Duration(Duration),
+}
+
+// adds an activity that has already been completed
+pub fn add(
+    file_name: &str,
+    project_name: &str,
+    activity_description: &str,
+    tags: &[String],
+    start: NaiveDateTime,
+    end: ActivityEnd,
+) -> Result<()> {
+    if matches!(end, ActivityEnd::Time(end) if end < start) {
+        bail!("The end of an activity must not be before its start");
+    }
+
+    let _lock = lock_for_changes(file_name)?;
+
+    let mut file_content = bartib_file::get_file_content(file_name).unwrap_or_default();
+
+    let mut activity = activity::Activity::start(
+        project_name.to_string(),
+        activity_description.to_string(),
+        Some(start),
+    );
+    match end {
+        ActivityEnd::Time(end) => activity.stop(Some(end)),
+        // the duration is added in UTC, so it is kept when the clocks are changed in between
+        ActivityEnd::Duration(duration) => activity.set_duration(duration),
+    }
+    activity.tags = tags.to_vec();
+
+    for overlapping_activity in getter::get_overlapping_activities(&file_content, &activity) {
+        eprintln!(
+            "Warning: The activity overlaps with \"{}\" ({}) started at {}",
+            overlapping_activity.description,
+            overlapping_activity.project,
+            overlapping_activity.start.format(conf::FORMAT_DATETIME)
+        );
+    }
+
+    println!(
+        "Added activity: \"{}\" ({}) from {} to {} ({}){}",
+        activity.description,
+        activity.project,
+        activity.start.format(conf::FORMAT_DATETIME),
+        activity.get_end_or_now().format(conf::FORMAT_DATETIME),
+        format_util::format_duration(&activity.get_duration()),
+        format_tags_suffix(&activity.tags)
+    );
+
+    file_content.push(bartib_file::Line::for_activity(activity));
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
+
 fn save_new_activity(
     file_name: &str,
-    file_content: &mut Vec<bartib_file::Line>,
+    file_content: &mut bartib_file::FileContent,
     activity: activity::Activity,
 ) -> Result<(), Error> {
     println!(
-        "Started activity: \"{}\" ({}) at {}",
+        "Started activity: \"{}\" ({}) at {}{}",
         activity.description,
         activity.project,
-        activity.start.format(conf::FORMAT_DATETIME)
+        activity.start.format(conf::FORMAT_DATETIME),
+        format_tags_suffix(&activity.tags)
     );
 
     file_content.push(bartib_file::Line::for_activity(activity));
@@ -56,8 +121,10 @@ pub fn change(
     file_name: &str,
     project_name: Option<&str>,
     activity_description: Option<&str>,
+    tags: Option<&[String]>,
     time: Option<NaiveDateTime>,
 ) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
     let mut file_content = bartib_file::get_file_content(file_name)?;
 
     for line in &mut file_content {
@@ -75,17 +142,23 @@ pub fn change(
                     changed = true;
                 }
 
+                if let Some(tags) = tags {
+                    activity.tags = tags.to_vec();
+                    changed = true;
+                }
+
                 if let Some(time) = time {
-                    activity.start = time;
+                    activity.set_start(time);
                     changed = true;
                 }
 
                 if changed {
                     println!(
-                        "Changed activity: \"{}\" ({}) started at {}",
+                        "Changed activity: \"{}\" ({}) started at {}{}",
                         activity.description,
                         activity.project,
-                        activity.start.format(conf::FORMAT_DATETIME)
+                        activity.start.format(conf::FORMAT_DATETIME),
+                        format_tags_suffix(&activity.tags)
                     );
                     line.set_changed();
                 }
@@ -98,47 +171,182 @@ pub fn change(
 
 // stops all currently running activities
 pub fn stop(file_name: &str, time: Option<NaiveDateTime>) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
     let mut file_content = bartib_file::get_file_content(file_name)?;
-    stop_all_running_activities(&mut file_content, time);
+
+    if getter::get_running_activities(&file_content).is_empty() {
+        discard_paused_activities(&mut file_content);
+    } else {
+        stop_all_running_activities(&mut file_content, time);
+    }
+
     bartib_file::write_to_file(file_name, &file_content)
         .context(format!("Could not write to file: {file_name}"))
 }
 
 // cancels all currently running activities
 pub fn cancel(file_name: &str) -> Result<()> {
-    let file_content = bartib_file::get_file_content(file_name)?;
-    let mut new_file_content: Vec<bartib_file::Line> = Vec::new();
+    let _lock = lock_for_changes(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name)?;
 
-    for line in file_content {
-        match &line.activity {
-            Ok(activity) => {
-                if activity.is_stopped() {
-                    new_file_content.push(line);
-                } else {
-                    println!(
-                        "Canceled activity: \"{}\" ({}) started at {}",
-                        activity.description,
-                        activity.project,
-                        activity.start.format(conf::FORMAT_DATETIME)
-                    );
-                }
-            }
-            Err(_) => new_file_content.push(line),
+    file_content.retain(|line| match &line.activity {
+        Ok(activity) if !activity.is_stopped() => {
+            println!(
+                "Canceled activity: \"{}\" ({}) started at {}",
+                activity.description,
+                activity.project,
+                activity.start.format(conf::FORMAT_DATETIME)
+            );
+            false
         }
+        _ => true,
+    });
+
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
+
+// changes an activity identified by its index
+//
+// the index of an activity is the number of the line it has been read from (see subcommand
+// `list`). All other lines are kept untouched.
+pub fn amend(
+    file_name: &str,
+    index: usize,
+    project_name: Option<&str>,
+    activity_description: Option<&str>,
+    tags: Option<&[String]>,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let position = get_position_of_index(&file_content, index)?;
+    let line = &mut file_content[position];
+    let activity = line.activity.as_mut().unwrap();
+
+    if let Some(p) = project_name {
+        activity.project = p.to_string();
     }
 
-    bartib_file::write_to_file(file_name, &new_file_content)
+    if let Some(d) = activity_description {
+        activity.description = d.to_string();
+    }
+
+    if let Some(t) = tags {
+        activity.tags = t.to_vec();
+    }
+
+    if let Some(s) = start {
+        activity.set_start(s);
+    }
+
+    if let Some(e) = end {
+        activity.set_end(Some(e));
+    }
+
+    if activity.end.is_some_and(|e| e < activity.start) {
+        bail!("The end of an activity must not be before its start");
+    }
+
+    println!(
+        "Amended activity: \"{}\" ({}) started at {}{}",
+        activity.description,
+        activity.project,
+        activity.start.format(conf::FORMAT_DATETIME),
+        format_tags_suffix(&activity.tags)
+    );
+
+    line.set_changed();
+    bartib_file::write_to_file(file_name, &file_content)
         .context(format!("Could not write to file: {file_name}"))
 }
 
+// sets the notes of an activity identified by its index or, without index, of the running
+// activity. Empty notes remove the notes
+pub fn note(file_name: &str, index: Option<usize>, notes: &str) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let position = match index {
+        Some(index) => get_position_of_index(&file_content, index)?,
+        // the latest running activity, if there are several of them
+        None => file_content
+            .iter()
+            .rposition(|line| line.activity.as_ref().is_ok_and(|a| !a.is_stopped()))
+            .context("No activity is running. Please specify the index of an activity")?,
+    };
+    let line = &mut file_content[position];
+    let activity = line.activity.as_mut().unwrap();
+
+    activity.set_notes(notes);
+
+    println!(
+        "{} activity: \"{}\" ({}) started at {}",
+        if activity.notes.is_some() {
+            "Set notes of"
+        } else {
+            "Removed notes of"
+        },
+        activity.description,
+        activity.project,
+        activity.start.format(conf::FORMAT_DATETIME)
+    );
+
+    line.set_changed();
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
+
+// removes an activity identified by its index from the file
+//
+// the lines following the activity move up, so their indexes decrease by one
+pub fn delete(file_name: &str, index: usize) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let position = get_position_of_index(&file_content, index)?;
+    let line = file_content.remove(position);
+
+    if let Ok(activity) = &line.activity {
+        println!(
+            "Deleted activity: \"{}\" ({}) started at {}",
+            activity.description,
+            activity.project,
+            activity.start.format(conf::FORMAT_DATETIME)
+        );
+    }
+
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
+
+// finds the position of the line with the given index that must contain a valid activity
+fn get_position_of_index(file_content: &[bartib_file::Line], index: usize) -> Result<usize> {
+    let position = file_content
+        .iter()
+        .position(|line| line.line_number == Some(index))
+        .context(format!("There is no activity with index {index}"))?;
+
+    if file_content[position].kind != bartib_file::LineKind::Activity {
+        bail!("Line {index} does not contain an activity");
+    }
+
+    if file_content[position].activity.is_err() {
+        bail!("Line {index} does not contain a valid activity. Please see `bartib check` for further information");
+    }
+
+    Ok(position)
+}
+
 // continue last activity
 pub fn continue_last_activity(
     file_name: &str,
     project_name: Option<&str>,
     activity_description: Option<&str>,
+    tags: Option<&[String]>,
     time: Option<NaiveDateTime>,
     number: usize,
 ) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
     let mut file_content = bartib_file::get_file_content(file_name)?;
 
     let descriptions_and_projects: Vec<(&String, &String)> =
@@ -162,11 +370,24 @@ pub fn continue_last_activity(
     let optional_description_and_project = descriptions_and_projects.get(i);
 
     if let Some((description, project)) = optional_description_and_project {
-        let new_activity = activity::Activity::start(
+        // without explicit tags the new activity takes the tags from the last time the continued
+        // activity has been tracked
+        let new_tags = match tags {
+            Some(tags) => tags.to_vec(),
+            None => getter::get_activities(&file_content)
+                .filter(|a| &a.description == *description && &a.project == *project)
+                .max_by_key(|a| a.start)
+                .map(|a| a.tags.clone())
+                .unwrap_or_default(),
+        };
+
+        let mut new_activity = activity::Activity::start(
             project_name.unwrap_or(project).to_string(),
             activity_description.unwrap_or(description).to_string(),
             time,
         );
+        new_activity.tags = new_tags;
+
         stop_all_running_activities(&mut file_content, time);
         save_new_activity(file_name, &mut file_content, new_activity)
     } else {
@@ -177,16 +398,255 @@ pub fn continue_last_activity(
     }
 }
 
+// pauses all currently running activities
+//
+// the activities are stopped and marked as paused, so they can be resumed later
+pub fn pause(file_name: &str, time: Option<NaiveDateTime>) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+
+    if getter::get_running_activities(&file_content).is_empty() {
+        bail!("No activity is running that could be paused");
+    }
+
+    for line in &mut file_content {
+        if let Ok(activity) = &mut line.activity {
+            if !activity.is_stopped() {
+                activity.stop(time);
+                activity.paused = true;
+                println!(
+                    "Paused activity: \"{}\" ({}) started at {} ({})",
+                    activity.description,
+                    activity.project,
+                    activity.start.format(conf::FORMAT_DATETIME),
+                    format_util::format_duration(&activity.get_duration()),
+                );
+
+                line.set_changed();
+            }
+        }
+    }
+
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
+
+// resumes all paused activities
+//
+// for every paused activity a new activity with the same project, description and tags is started.
+// It is marked as resumed, so both segments are shown as parts of the same activity
+pub fn resume(file_name: &str, time: Option<NaiveDateTime>) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+
+    let paused_activities: Vec<activity::Activity> = getter::get_paused_activities(&file_content)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    if paused_activities.is_empty() {
+        bail!("No activity has been paused");
+    }
+
+    stop_all_running_activities(&mut file_content, time);
+
+    for paused_activity in paused_activities {
+        let mut activity =
+            activity::Activity::start(paused_activity.project, paused_activity.description, time);
+        activity.tags = paused_activity.tags;
+        activity.resumed = true;
+
+        println!(
+            "Resumed activity: \"{}\" ({}) at {}",
+            activity.description,
+            activity.project,
+            activity.start.format(conf::FORMAT_DATETIME)
+        );
+
+        file_content.push(bartib_file::Line::for_activity(activity));
+    }
+
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
+
 pub fn start_editor(file_name: &str, optional_editor_command: Option<&str>) -> Result<()> {
     let editor_command = optional_editor_command.context("editor command is missing")?;
-    let command = Command::new(editor_command).arg(file_name).spawn();
 
-    match command {
-        Ok(mut child) => {
-            child.wait().context("editor did not execute")?;
-            Ok(())
+    // the log is edited in a copy, so it does not need to be locked while the editor is open
+    let previous_lines = bartib_file::read_lines(file_name).unwrap_or_default();
+    let copy_path = bartib_file::create_copy(file_name)
+        .context(format!("Could not create a copy of file: {file_name}"))?;
+    let copy_name = copy_path.to_string_lossy();
+
+    let mut child = Command::new(editor_command)
+        .arg(copy_path.as_os_str())
+        .spawn()
+        .map_err(|e| anyhow!(e))?;
+    child.wait().context("editor did not execute")?;
+
+    let lines = bartib_file::read_lines(&copy_name)
+        .context(format!("Could not read the edited copy: {copy_name}"))?;
+
+    let _lock = lock_for_changes(file_name)?;
+    if bartib_file::read_lines(file_name).unwrap_or_default() != previous_lines {
+        bail!(
+            "The file {file_name} has been changed while it was edited. Your changes are kept in {copy_name}"
+        );
+    }
+
+    bartib_file::write_lines(file_name, &lines)
+        .context(format!("Could not write to file: {file_name}"))?;
+    // changes made in the editor may be undone as well
+    journal::warn_on_error(journal::record(file_name, &previous_lines, &lines));
+
+    std::fs::remove_file(&copy_path)
+        .context(format!("Could not remove the edited copy: {copy_name}"))
+}
+
+// reverts the latest change to the bartib file
+pub fn undo(file_name: &str) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let change = journal::pop(file_name, &journal::Journal::Undo)
+        .context("Could not read the journal")?
+        .context("There is nothing to undo")?;
+    let lines = bartib_file::read_lines(file_name)?;
+
+    let Some(new_lines) = change.revert(&lines) else {
+        journal::push(file_name, &journal::Journal::Undo, &change)?;
+        bail!("The file has been changed in the meantime. The change can not be undone");
+    };
+
+    bartib_file::write_lines(file_name, &new_lines)
+        .context(format!("Could not write to file: {file_name}"))?;
+    journal::warn_on_error(journal::push(file_name, &journal::Journal::Redo, &change));
+
+    println!(
+        "Undone change from {}",
+        change.time.format(conf::FORMAT_DATETIME)
+    );
+    print_changed_lines("Removed", &change.added);
+    print_changed_lines("Restored", &change.removed);
+
+    Ok(())
+}
+
+// applies the latest change that has been undone again
+pub fn redo(file_name: &str) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let change = journal::pop(file_name, &journal::Journal::Redo)
+        .context("Could not read the journal")?
+        .context("There is nothing to redo")?;
+    let lines = bartib_file::read_lines(file_name).unwrap_or_default();
+
+    let Some(new_lines) = change.apply(&lines) else {
+        journal::push(file_name, &journal::Journal::Redo, &change)?;
+        bail!("The file has been changed in the meantime. The change can not be redone");
+    };
+
+    bartib_file::write_lines(file_name, &new_lines)
+        .context(format!("Could not write to file: {file_name}"))?;
+    journal::warn_on_error(journal::push(file_name, &journal::Journal::Undo, &change));
+
+    println!(
+        "Redone change from {}",
+        change.time.format(conf::FORMAT_DATETIME)
+    );
+    print_changed_lines("Removed", &change.removed);
+    print_changed_lines("Restored", &change.added);
+
+    Ok(())
+}
+
+// replaces the content of the bartib file with one of its backups. The change may be undone
+pub fn restore(file_name: &str, number: usize) -> Result<()> {
+    let _lock = lock_for_changes(file_name)?;
+    let backup = bartib_file::get_backups(file_name)
+        .into_iter()
+        .find(|backup| backup.number == number)
+        .context(format!("There is no backup with number {number}"))?;
+    let backup_lines = bartib_file::read_lines(&backup.path.to_string_lossy())
+        .context(format!("Could not read backup: {}", backup.path.display()))?;
+    let lines = bartib_file::read_lines(file_name).unwrap_or_default();
+
+    bartib_file::write_lines_without_backup(file_name, &backup_lines)
+        .context(format!("Could not write to file: {file_name}"))?;
+    journal::warn_on_error(journal::record(file_name, &lines, &backup_lines));
+
+    match backup.time {
+        Some(time) => println!(
+            "Restored backup {number} from {}",
+            time.format(conf::FORMAT_DATETIME)
+        ),
+        None => println!("Restored backup {number}"),
+    }
+
+    Ok(())
+}
+
+// prints the activities in lines that have been removed from or restored to the file
+fn print_changed_lines(action: &str, lines: &[String]) {
+    for line in lines {
+        match activity::Activity::from_str(line) {
+            Ok(activity) => println!(
+                "{action} activity: \"{}\" ({}) started at {}",
+                activity.description,
+                activity.project,
+                activity.start.format(conf::FORMAT_DATETIME)
+            ),
+            Err(_) => println!("{action} line: {line}"),
+        }
+    }
+}
+
+// formats the tags of an activity to be appended to a status message
+fn format_tags_suffix(tags: &[String]) -> String {
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", activity::format_tags(tags))
+    }
+}
+
+// locks the activity log until the lock is dropped, so that concurrent invocations of bartib do
+// not overwrite each other's changes
+pub fn lock_for_changes(file_name: &str) -> Result<bartib_file::FileLock> {
+    match bartib_file::lock(
+        file_name,
+        &bartib_file::LockKind::Exclusive,
+        conf::LOCK_TIMEOUT,
+    ) {
+        Ok(lock) => Ok(lock),
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => bail!(
+            "The file {file_name} is in use by another bartib process. Please try again later"
+        ),
+        Err(e) => Err(e).context(format!("Could not lock file: {file_name}")),
+    }
+}
+
+// removes the pause marker from the activities which are still paused
+//
+// activities which have already been resumed keep their marker, so their lines stay unchanged
+fn discard_paused_activities(file_content: &mut [bartib_file::Line]) {
+    let paused: Vec<(NaiveDateTime, Option<NaiveDateTime>)> =
+        getter::get_paused_activities(file_content)
+            .iter()
+            .map(|activity| (activity.start, activity.end))
+            .collect();
+
+    for line in file_content {
+        if let Ok(activity) = &mut line.activity {
+            if activity.paused && paused.contains(&(activity.start, activity.end)) {
+                activity.paused = false;
+                println!(
+                    "Discarded pause of activity: \"{}\" ({}) started at {}",
+                    activity.description,
+                    activity.project,
+                    activity.start.format(conf::FORMAT_DATETIME),
+                );
+                line.set_changed();
+            }
         }
-        Err(e) => Err(anyhow!(e)),
     }
 }
 
@@ -211,3 +671,222 @@ fn stop_all_running_activities(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::data::bartib_file::tests::TestFile;
+
+    static CONTENT: &str = "# comment\r\n\
+        2024-03-01 09:00 - 2024-03-01 10:00 |  p1 | first\r\n\
+        2024-03-01 10:00 - 2024-03-01 11:00 | p1 | second | +tag\n\
+        \n\
+        2024-03-01 | p1 | invalid\n\
+        2024-03-01 12:00 - 2024-03-01 13:00 | p2 | third\n";
+
+    // runs a manipulation on a copy of CONTENT and returns its result and the resulting file
+    fn manipulate(name: &str, f: impl Fn(&str) -> Result<()>) -> (Result<()>, String) {
+        let file = TestFile::new(&format!("manipulation_{name}"));
+        fs::write(&file.name, CONTENT).unwrap();
+
+        let result = f(&file.name);
+        let content = fs::read_to_string(&file.name).unwrap();
+
+        (result, content)
+    }
+
+    fn datetime(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%F %R").unwrap()
+    }
+
+    #[test]
+    fn amend_changes_only_the_target_line() {
+        let (result, content) = manipulate("amend", |file_name| {
+            amend(file_name, 3, None, Some("changed"), None, None, None)
+        });
+
+        assert!(result.is_ok());
+        let lines: Vec<&str> = CONTENT.split_inclusive('\n').collect();
+        let amended: Vec<&str> = content.split_inclusive('\n').collect();
+        assert_eq!(amended.len(), lines.len());
+        for (i, (line, amended_line)) in lines.iter().zip(&amended).enumerate() {
+            if i == 2 {
+                // the timestamps are written with the precision of the build
+                let activity = activity::Activity::from_str(amended_line.trim_end()).unwrap();
+                assert_eq!(activity.description, "changed");
+                assert_eq!(activity.project, "p1");
+                assert_eq!(activity.tags, vec!["tag".to_string()]);
+                assert_eq!(activity.start, datetime("2024-03-01 10:00"));
+                assert_eq!(activity.end, Some(datetime("2024-03-01 11:00")));
+            } else {
+                assert_eq!(line, amended_line);
+            }
+        }
+    }
+
+    #[test]
+    fn add_with_duration() {
+        let (result, content) = manipulate("add_duration", |file_name| {
+            add(
+                file_name,
+                "p3",
+                "fourth",
+                &[],
+                datetime("2024-03-01 14:00"),
+                ActivityEnd::Duration(Duration::minutes(90)),
+            )
+        });
+
+        assert!(result.is_ok());
+        let activity = activity::Activity::from_str(content[CONTENT.len()..].trim_end()).unwrap();
+        assert_eq!(activity.end, Some(datetime("2024-03-01 15:30")));
+    }
+
+    #[test]
+    fn amend_rejects_end_before_start() {
+        let (result, content) = manipulate("amend_end", |file_name| {
+            amend(
+                file_name,
+                2,
+                None,
+                None,
+                None,
+                None,
+                Some(datetime("2024-03-01 08:00")),
+            )
+        });
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("must not be before its start"));
+        assert_eq!(content, CONTENT);
+    }
+
+    #[test]
+    fn delete_shifts_following_indexes() {
+        let (result, content) = manipulate("delete", |file_name| {
+            delete(file_name, 2)?;
+            // the second activity has moved up to index 2
+            delete(file_name, 2)
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(
+            content,
+            "# comment\r\n\n2024-03-01 | p1 | invalid\n2024-03-01 12:00 - 2024-03-01 13:00 | p2 | third\n"
+        );
+    }
+
+    #[test]
+    fn index_out_of_range() {
+        let (result, content) = manipulate("out_of_range", |file_name| delete(file_name, 7));
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("There is no activity with index 7"));
+        assert_eq!(content, CONTENT);
+    }
+
+    #[test]
+    fn index_of_lines_without_valid_activity() {
+        let file_content: Vec<bartib_file::Line> = CONTENT
+            .lines()
+            .enumerate()
+            .map(|(i, line)| bartib_file::Line::new(line, i + 1))
+            .collect();
+
+        assert_eq!(get_position_of_index(&file_content, 6).unwrap(), 5);
+        assert!(get_position_of_index(&file_content, 0).is_err());
+
+        let comment = get_position_of_index(&file_content, 1).unwrap_err();
+        assert!(comment.to_string().contains("does not contain an activity"));
+
+        let blank = get_position_of_index(&file_content, 4).unwrap_err();
+        assert!(blank.to_string().contains("does not contain an activity"));
+
+        let invalid = get_position_of_index(&file_content, 5).unwrap_err();
+        assert!(invalid
+            .to_string()
+            .contains("does not contain a valid activity"));
+    }
+
+    #[test]
+    fn amend_unparseable_line() {
+        let (result, content) = manipulate("amend_invalid", |file_name| {
+            amend(file_name, 5, Some("p3"), None, None, None, None)
+        });
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("does not contain a valid activity"));
+        assert_eq!(content, CONTENT);
+    }
+
+    #[test]
+    fn pause_and_resume_append_linked_segments() {
+        let (result, content) = manipulate("pause_resume", |file_name| {
+            start(
+                file_name,
+                "p3",
+                "fourth",
+                &[],
+                None,
+                Some(datetime("2024-03-01 14:00")),
+            )?;
+            pause(file_name, Some(datetime("2024-03-01 15:00")))?;
+            resume(file_name, Some(datetime("2024-03-01 16:00")))?;
+            start(
+                file_name,
+                "p2",
+                "fifth",
+                &[],
+                None,
+                Some(datetime("2024-03-01 17:00")),
+            )
+        });
+
+        assert!(result.is_ok());
+        assert!(content.starts_with(CONTENT));
+
+        let activities: Vec<activity::Activity> = content[CONTENT.len()..]
+            .lines()
+            .map(|line| activity::Activity::from_str(line).unwrap())
+            .collect();
+        assert_eq!(activities.len(), 3);
+        assert!(activities[0].paused && !activities[0].resumed);
+        assert_eq!(activities[0].end, Some(datetime("2024-03-01 15:00")));
+        assert!(!activities[1].paused && activities[1].resumed);
+        assert_eq!(activities[1].description, "fourth");
+        assert_eq!(activities[1].end, Some(datetime("2024-03-01 17:00")));
+        assert!(!activities[2].paused && !activities[2].resumed);
+    }
+
+    #[test]
+    fn stop_discards_the_pause() {
+        let (result, content) = manipulate("pause_stop", |file_name| {
+            start(
+                file_name,
+                "p3",
+                "fourth",
+                &[],
+                None,
+                Some(datetime("2024-03-01 14:00")),
+            )?;
+            pause(file_name, Some(datetime("2024-03-01 15:00")))?;
+            stop(file_name, None)?;
+            resume(file_name, None)
+        });
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("No activity has been paused"));
+        let activity = activity::Activity::from_str(content[CONTENT.len()..].trim_end()).unwrap();
+        assert!(!activity.paused);
+    }
+}
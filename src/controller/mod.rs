@@ -1,3 +1,6 @@
+pub mod balance;
+pub mod export;
+pub mod import;
 pub mod list;
 pub mod manipulation;
 pub mod report;
@@ -4,12 +4,16 @@ use crate::data::activity;
 use crate::data::bartib_file;
 use crate::data::getter;
 use crate::data::processor;
+use crate::data::round_util::Rounding;
 use crate::view::report;
 
 pub fn show_report(
     file_name: &str,
     filter: getter::ActivityFilter,
     processors: processor::ProcessorList,
+    grouping: &report::ReportGrouping,
+    billing: Option<&report::Billing>,
+    total_rounding: Option<&Rounding>,
 ) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
     let activities = getter::get_activities(&file_content).collect();
@@ -18,18 +22,40 @@ pub fn show_report(
         processor::process_activities(activities, processors);
     let processed_activities: Vec<&activity::Activity> = processed_activities_bind.iter().collect();
 
-    let mut filtered_activities: Vec<&activity::Activity> =
-        getter::filter_activities(processed_activities, &filter);
+    // activities are trimmed to the date range, so the report only contains the time spent
+    // within the range
+    let mut clipped_activities_bind: Vec<activity::Activity> =
+        getter::clip_activities(processed_activities, &filter);
 
-    filtered_activities.sort_by_key(|activity| activity.start);
+    clipped_activities_bind.sort_by_key(|activity| activity.start);
 
-    let first_element = filtered_activities.len().saturating_sub(
+    let first_element = clipped_activities_bind.len().saturating_sub(
         filter
             .number_of_activities
-            .unwrap_or(filtered_activities.len()),
+            .unwrap_or(clipped_activities_bind.len()),
     );
-
-    report::show_activities(&filtered_activities[first_element..filtered_activities.len()]);
+    clipped_activities_bind.drain(..first_element);
+
+    // the totals are rounded after all activities of the report are known. They are grouped
+    // like in the report. An activity with several tags is rounded with its first tag
+    if let Some(rounding) = total_rounding {
+        clipped_activities_bind = match grouping {
+            report::ReportGrouping::Project => {
+                processor::round_totals(clipped_activities_bind, rounding, |activity| {
+                    (activity.project.clone(), activity.description.clone())
+                })
+            }
+            report::ReportGrouping::Tag => {
+                processor::round_totals(clipped_activities_bind, rounding, |activity| {
+                    (activity.tags.first().cloned(), activity.description.clone())
+                })
+            }
+        };
+    }
+
+    let filtered_activities: Vec<&activity::Activity> = clipped_activities_bind.iter().collect();
+
+    report::show_activities(&filtered_activities, grouping, billing);
 
     Ok(())
 }
@@ -1,20 +1,25 @@
 use anyhow::Result;
-use chrono::Local;
+use chrono::{Datelike, Duration, Local, NaiveDate};
 
+use crate::conf;
 use crate::data::activity;
 use crate::data::activity::Activity;
 use crate::data::bartib_file;
+use crate::data::filter;
 use crate::data::filter::Filters;
 use crate::data::getter;
 use crate::data::processor;
-use crate::data::processor::StatusReportData;
+use crate::data::processor::{StatusReportData, StatusTargets};
+use crate::data::working_time;
 
 pub fn show_status(
     file_name: &str,
     filter: getter::ActivityFilter,
     processors: processor::ProcessorList,
     writer: &dyn processor::StatusReportWriter,
+    config: &conf::Config,
 ) -> Result<()> {
+    let week_start = config.get_week_start();
     let file_content = bartib_file::get_file_content(file_name)?;
     let activities: Vec<&Activity> = getter::get_activities(&file_content).collect();
 
@@ -22,6 +27,23 @@ pub fn show_status(
         processor::process_activities(activities, processors);
     let processed_activities: Vec<&activity::Activity> = processed_activities_bind.iter().collect();
 
+    // a date range given on the command line gets a total of its own. The other totals are
+    // always shown for the periods they are named after
+    let range = (filter.date.is_some() || filter.from_date.is_some() || filter.to_date.is_some())
+        .then(|| {
+            getter::clip_activities(processed_activities.clone(), &filter)
+                .iter()
+                .map(activity::Activity::get_duration)
+                .sum()
+        });
+
+    let filter = getter::ActivityFilter {
+        from_date: None,
+        to_date: None,
+        date: None,
+        ..filter
+    };
+
     let mut filtered_activities: Vec<&activity::Activity> =
         getter::filter_activities(processed_activities, &filter);
 
@@ -36,6 +58,10 @@ pub fn show_status(
         .take(1)
         .last();
 
+    let paused: Option<&Activity> = getter::filter_paused_activities(&filtered_activities)
+        .last()
+        .copied();
+
     let today = filtered_activities
         .clone()
         .into_iter()
@@ -46,10 +72,15 @@ pub fn show_status(
     let current_week = filtered_activities
         .clone()
         .into_iter()
-        .filter(Filters::current_week(now.date()))
+        .filter(Filters::current_week(now.date(), week_start))
         .map(|f| f.get_duration())
         .sum();
 
+    let targets = config
+        .working_time
+        .as_ref()
+        .map(|working_time| get_targets(working_time, &filtered_activities, now.date(), config));
+
     let current_month = filtered_activities
         .into_iter()
         .filter(Filters::current_month(now.date()))
@@ -58,10 +89,33 @@ pub fn show_status(
 
     let status_report_data = StatusReportData {
         activity: current,
+        paused,
         today,
         current_week,
         current_month,
+        range,
         project: filter.project,
+        targets,
     };
     writer.process(&status_report_data)
 }
+
+// calculates the target working times up to and including today
+fn get_targets(
+    working_time: &conf::WorkingTime,
+    activities: &[&Activity],
+    today: NaiveDate,
+    config: &conf::Config,
+) -> StatusTargets {
+    let first_day_of_week = filter::first_day_of_week(today, config.get_week_start());
+    let first_day_of_month = today - Duration::days(i64::from(today.day0()));
+
+    StatusTargets {
+        today: working_time::get_target(working_time, today, today),
+        current_week: working_time::get_target(working_time, first_day_of_week, today),
+        current_month: working_time::get_target(working_time, first_day_of_month, today),
+        balance: working_time.start.map(|start| {
+            working_time::Period::new(working_time, activities, start, today).get_balance()
+        }),
+    }
+}
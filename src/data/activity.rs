@@ -2,20 +2,32 @@
 use chrono::DurationRound;
 #[cfg(feature = "second-precision")]
 use chrono::Timelike;
-use chrono::{Duration, Local, NaiveDateTime};
+use chrono::{Duration, FixedOffset, Local, NaiveDateTime, Offset, TimeZone, Utc};
 use std::fmt;
 use std::str::{Chars, FromStr};
 use thiserror::Error;
 
 use crate::conf;
+use crate::data::timezone;
 
 #[derive(Debug, Clone)]
 pub struct Activity {
     pub start: NaiveDateTime,
     pub end: Option<NaiveDateTime>,
+    // the offsets to UTC the start and the end have been recorded with. Timestamps without
+    // offset, e.g. from files of older versions, are interpreted in the local time zone
+    pub start_offset: Option<FixedOffset>,
+    pub end_offset: Option<FixedOffset>,
 
     pub project: String,
     pub description: String,
+    pub tags: Vec<String>,
+    // the activity has been interrupted by a pause
+    pub paused: bool,
+    // the activity continues a paused activity with the same project and description
+    pub resumed: bool,
+    // longer notes on the activity, e.g. links or outcomes, which are not shown in reports
+    pub notes: Option<String>,
 }
 
 #[derive(Error, Debug)]
@@ -24,21 +36,81 @@ pub enum ActivityError {
     DateTimeParseError,
     #[error("could not parse activity")]
     GeneralParseError,
+    #[error("line does not contain an activity")]
+    NoActivity,
 }
 
 impl Activity {
     #[must_use]
     pub fn start(project: String, description: String, time: Option<NaiveDateTime>) -> Self {
+        let (start, start_offset) = time.map_or_else(now, local);
+
         Self {
-            start: time.unwrap_or_else(|| Local::now().naive_local()),
+            start,
             end: None,
+            start_offset,
+            end_offset: None,
             project,
             description,
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
         }
     }
 
     pub fn stop(&mut self, time: Option<NaiveDateTime>) {
-        self.end = time.or_else(|| Some(Local::now().naive_local()));
+        let (end, end_offset) = time.map_or_else(now, local);
+        self.end = Some(end);
+        self.end_offset = end_offset;
+    }
+
+    // sets the start to a time in the local time zone
+    pub fn set_start(&mut self, start: NaiveDateTime) {
+        (self.start, self.start_offset) = local(start);
+    }
+
+    // sets the end to a time in the local time zone
+    pub fn set_end(&mut self, end: Option<NaiveDateTime>) {
+        self.end = end;
+        self.end_offset = end.and_then(|end| local(end).1);
+    }
+
+    // moves the start to another time in the time zone the start has been recorded in
+    pub fn move_start(&mut self, start: NaiveDateTime) {
+        self.start_offset = timezone::move_offset(&Local, &self.start, self.start_offset, &start);
+        self.start = start;
+    }
+
+    // moves the end to another time in the time zone the start has been recorded in
+    pub fn move_end(&mut self, end: NaiveDateTime) {
+        self.end_offset = timezone::move_offset(&Local, &self.start, self.start_offset, &end);
+        self.end = Some(end);
+    }
+
+    // moves the end, so the activity lasts for the given duration
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.set_duration_in(&Local, duration);
+    }
+
+    // moves the end, so the activity lasts for the given duration in the given time zone
+    pub fn set_duration_in<Tz: TimeZone>(&mut self, tz: &Tz, duration: Duration) {
+        let (end, end_offset) =
+            timezone::add_duration(tz, &self.start, self.start_offset, duration);
+        self.end = Some(end);
+        self.end_offset = end_offset;
+    }
+
+    // sets the notes of the activity. Line breaks are replaced, as an activity is written to a
+    // single line. Empty notes remove the notes
+    pub fn set_notes(&mut self, notes: &str) {
+        let notes = notes.replace("\r\n", " ").replace(['\n', '\r'], " ");
+        let notes = notes.trim();
+        self.notes = if notes.is_empty() {
+            None
+        } else {
+            Some(notes.to_string())
+        };
     }
 
     #[must_use]
@@ -46,13 +118,64 @@ impl Activity {
         self.end.is_some()
     }
 
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    // checks whether the time spans of two activities overlap. Running activities are
+    // considered to last until now
+    #[must_use]
+    pub fn overlaps(&self, other: &Activity) -> bool {
+        self.start < other.get_end_or_now() && other.start < self.get_end_or_now()
+    }
+
+    // the end of the activity or, if it is still running, the current time in the time zone
+    // of its start
+    #[must_use]
+    pub fn get_end_or_now(&self) -> NaiveDateTime {
+        self.end.unwrap_or_else(|| match self.start_offset {
+            Some(offset) => Utc::now().with_timezone(&offset).naive_local(),
+            None => Local::now().naive_local(),
+        })
+    }
+
     #[must_use]
     pub fn get_duration(&self) -> Duration {
-        if let Some(end) = self.end {
-            end.signed_duration_since(self.start)
-        } else {
-            Local::now().naive_local().signed_duration_since(self.start)
-        }
+        self.get_duration_in(&Local)
+    }
+
+    // the duration of the activity if its timestamps are interpreted in the given time zone
+    //
+    // the duration is calculated in UTC, so it is correct even if the clocks have been changed
+    // while the activity was running
+    #[must_use]
+    pub fn get_duration_in<Tz: TimeZone>(&self, tz: &Tz) -> Duration {
+        let end = self.end.map_or_else(
+            || Utc::now().naive_utc(),
+            |end| timezone::to_utc(tz, &end, self.end_offset),
+        );
+
+        end.signed_duration_since(timezone::to_utc(tz, &self.start, self.start_offset))
+    }
+}
+
+// the current local time together with its offset
+fn now() -> (NaiveDateTime, Option<FixedOffset>) {
+    let now = Local::now();
+    (now.naive_local(), Some(now.offset().fix()))
+}
+
+// a time in the local time zone together with its offset
+fn local(time: NaiveDateTime) -> (NaiveDateTime, Option<FixedOffset>) {
+    (time, Some(timezone::get_offset(&Local, &time)))
+}
+
+// formats a timestamp for the activity log, followed by its offset if it has one
+fn format_datetime(datetime: &NaiveDateTime, offset: Option<FixedOffset>) -> String {
+    match offset {
+        Some(offset) => format!("{}{offset}", datetime.format(conf::FORMAT_DATETIME)),
+        None => datetime.format(conf::FORMAT_DATETIME).to_string(),
     }
 }
 
@@ -62,23 +185,75 @@ impl fmt::Display for Activity {
         let escaped_description = escape_special_chars(&self.description);
 
         match self.end {
-            None => writeln!(
+            None => write!(
                 f,
                 "{} | {} | {}",
-                self.start.format(conf::FORMAT_DATETIME),
+                format_datetime(&self.start, self.start_offset),
                 escaped_project_name,
                 escaped_description
-            ),
-            Some(end) => writeln!(
+            )?,
+            Some(end) => write!(
                 f,
                 "{} - {} | {} | {}",
-                self.start.format(conf::FORMAT_DATETIME),
-                end.format(conf::FORMAT_DATETIME),
+                format_datetime(&self.start, self.start_offset),
+                format_datetime(&end, self.end_offset),
                 escaped_project_name,
                 escaped_description
-            ),
+            )?,
+        }
+
+        // tags and the pause markers are written as an optional fourth part, so lines without
+        // them stay unchanged. Notes follow as fifth part
+        if !self.tags.is_empty() || self.paused || self.resumed || self.notes.is_some() {
+            let mut attributes = format_tags(&self.tags);
+
+            for (is_set, marker) in [(self.resumed, RESUMED_MARKER), (self.paused, PAUSED_MARKER)] {
+                if is_set {
+                    if !attributes.is_empty() {
+                        attributes.push(' ');
+                    }
+                    attributes.push_str(marker);
+                }
+            }
+
+            write!(f, " | {}", escape_special_chars(&attributes))?;
+        }
+
+        if let Some(notes) = &self.notes {
+            write!(f, " | {}", escape_special_chars(notes))?;
+        }
+
+        writeln!(f)
+    }
+}
+
+// marks a paused activity in the bartib file
+static PAUSED_MARKER: &str = "(paused)";
+
+// marks an activity in the bartib file which continues a paused one
+static RESUMED_MARKER: &str = "(resumed)";
+
+// formats a list of tags with the "+tag" syntax used in the bartib file
+#[must_use]
+pub fn format_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!("+{tag}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// parses a list of tags separated by whitespace. A leading "+" is optional
+#[must_use]
+pub fn parse_tags(s: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for tag in s.split_whitespace().map(|t| t.trim_start_matches('+')) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
         }
     }
+
+    tags
 }
 
 // escapes the pipe character, so we can use it to separate the distinct parts of a activity
@@ -98,29 +273,70 @@ impl FromStr for Activity {
 
         let time_parts: Vec<&str> = parts[0].split(" - ").collect();
 
-        let starttime = parse_timepart(time_parts[0])?;
-        let endtime: Option<NaiveDateTime> = if time_parts.len() > 1 {
-            Some(parse_timepart(time_parts[1])?)
+        let (starttime, start_offset) = parse_timepart(time_parts[0])?;
+        let (endtime, end_offset) = if time_parts.len() > 1 {
+            let (endtime, end_offset) = parse_timepart(time_parts[1])?;
+            (Some(endtime), end_offset)
         } else {
-            None
+            (None, None)
         };
 
         let project = parts[1].trim();
         let description = if parts.len() > 2 { parts[2].trim() } else { "" };
+        let attributes = if parts.len() > 3 {
+            parts[3].as_str()
+        } else {
+            ""
+        };
+        let notes = parts
+            .get(4)
+            .map(|notes| notes.trim())
+            .filter(|notes| !notes.is_empty());
+        let paused = attributes.split_whitespace().any(|a| a == PAUSED_MARKER);
+        let resumed = attributes.split_whitespace().any(|a| a == RESUMED_MARKER);
+        let tags = parse_tags(
+            &attributes
+                .split_whitespace()
+                .filter(|a| *a != PAUSED_MARKER && *a != RESUMED_MARKER)
+                .collect::<Vec<&str>>()
+                .join(" "),
+        );
 
         let activity = Self {
             start: starttime,
             end: endtime,
+            start_offset,
+            end_offset,
             project: project.to_string(),
             description: description.to_string(),
+            tags,
+            paused,
+            resumed,
+            notes: notes.map(str::to_string),
         };
 
         Ok(activity)
     }
 }
 
+// parses a timestamp with an optional offset to UTC, e.g. `2024-03-01 09:00+01:00`
+fn parse_timepart(time_part: &str) -> Result<(NaiveDateTime, Option<FixedOffset>), ActivityError> {
+    let time_part = time_part.trim();
+
+    // the date itself contains dashes, so an offset can only follow after it
+    match time_part.rfind(['+', '-']) {
+        Some(i) if i > "YYYY-MM-DD".len() => {
+            let offset = time_part[i..]
+                .parse()
+                .map_err(|_| ActivityError::DateTimeParseError)?;
+            Ok((parse_datetime(&time_part[..i])?, Some(offset)))
+        }
+        _ => Ok((parse_datetime(time_part)?, None)),
+    }
+}
+
 #[cfg(not(feature = "second-precision"))]
-fn parse_timepart(time_part: &str) -> Result<NaiveDateTime, ActivityError> {
+fn parse_datetime(time_part: &str) -> Result<NaiveDateTime, ActivityError> {
     match NaiveDateTime::parse_from_str(time_part.trim(), conf::FORMAT_DATETIME) {
         Ok(datetime) => Ok(datetime),
         Err(_) => {
@@ -148,7 +364,7 @@ fn parse_timepart(time_part: &str) -> Result<NaiveDateTime, ActivityError> {
 }
 
 #[cfg(feature = "second-precision")]
-fn parse_timepart(time_part: &str) -> Result<NaiveDateTime, ActivityError> {
+fn parse_datetime(time_part: &str) -> Result<NaiveDateTime, ActivityError> {
     match NaiveDateTime::parse_from_str(time_part.trim(), conf::FORMAT_DATETIME) {
         Ok(datetime) => Ok(datetime),
         Err(_) => {
@@ -223,6 +439,7 @@ fn split_with_escaped_delimiter(s: &str) -> StringSplitter<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::timezone::tests::Cet2024;
     use chrono::{Datelike, Timelike};
     use std::option::Option::None;
 
@@ -238,6 +455,27 @@ mod tests {
         assert_eq!(t.end, None);
     }
 
+    #[test]
+    fn start_and_stop_record_offset() {
+        let mut t = Activity::start("p".to_string(), "d".to_string(), None);
+        t.stop(None);
+        assert_eq!(t.start_offset, Some(*Local::now().offset()));
+        assert!(t.end_offset.is_some());
+
+        let time = NaiveDateTime::parse_from_str("2024-07-01 09:00", "%F %R").unwrap();
+        let t = Activity::start("p".to_string(), "d".to_string(), Some(time));
+        assert_eq!(
+            t.start_offset,
+            Some(
+                Local
+                    .offset_from_local_datetime(&time)
+                    .earliest()
+                    .unwrap()
+                    .fix()
+            )
+        );
+    }
+
     #[test]
     fn stop() {
         let mut t = Activity::start(
@@ -249,6 +487,26 @@ mod tests {
         assert_ne!(t.end, None);
     }
 
+    #[test]
+    fn overlaps() {
+        let a = Activity::from_str("2024-03-01 09:00 - 2024-03-01 10:00 | p | a").unwrap();
+        let b = Activity::from_str("2024-03-01 09:30 - 2024-03-01 10:30 | p | b").unwrap();
+        let c = Activity::from_str("2024-03-01 10:00 - 2024-03-01 11:00 | p | c").unwrap();
+        let running = Activity::from_str("2024-03-01 10:45 | p | running").unwrap();
+
+        assert!(a.overlaps(&b));
+        assert!(b.overlaps(&a));
+        assert!(b.overlaps(&c));
+
+        // activities which only touch each other do not overlap
+        assert!(!a.overlaps(&c));
+        assert!(!c.overlaps(&a));
+
+        // running activities last until now
+        assert!(running.overlaps(&c));
+        assert!(!running.overlaps(&b));
+    }
+
     #[test]
     #[cfg(not(feature = "second-precision"))]
     fn display() {
@@ -258,6 +516,7 @@ mod tests {
             None,
         );
         t.start = NaiveDateTime::parse_from_str("2021-02-16 16:14", conf::FORMAT_DATETIME).unwrap();
+        t.start_offset = None;
         assert_eq!(
             format!("{t}"),
             "2021-02-16 16:14 | test project\\| 1 | test\\\\description\n"
@@ -280,6 +539,7 @@ mod tests {
         );
         t.start =
             NaiveDateTime::parse_from_str("2021-02-16 16:14:53", conf::FORMAT_DATETIME).unwrap();
+        t.start_offset = None;
         assert_eq!(
             format!("{t}"),
             "2021-02-16 16:14:53 | test project\\| 1 | test\\\\description\n"
@@ -476,6 +736,174 @@ mod tests {
         assert_eq!(t.description, t2.description);
     }
 
+    #[test]
+    fn from_str_tags() {
+        let t = Activity::from_str(
+            "2021-02-16 16:14 - 2021-02-16 18:23 | test project | test description | +meeting +review",
+        )
+        .unwrap();
+
+        assert_eq!(t.description, "test description");
+        assert_eq!(t.tags, vec!["meeting".to_string(), "review".to_string()]);
+        assert!(t.has_tag("meeting"));
+        assert!(!t.has_tag("call"));
+    }
+
+    #[test]
+    fn from_str_without_tags() {
+        let t = Activity::from_str("2021-02-16 16:14 | test project | test description").unwrap();
+
+        assert!(t.tags.is_empty());
+    }
+
+    #[test]
+    fn tags_roundtrip() {
+        let mut t = Activity::start(
+            "test project".to_string(),
+            "test description".to_string(),
+            None,
+        );
+        t.tags = vec!["meeting".to_string(), "a|b".to_string()];
+        t.stop(None);
+
+        let line = format!("{t}");
+        assert!(line.ends_with(" | +meeting +a\\|b\n"));
+
+        let t2 = Activity::from_str(&line).unwrap();
+        assert_eq!(t.tags, t2.tags);
+        assert_eq!(t.description, t2.description);
+    }
+
+    #[test]
+    fn paused_roundtrip() {
+        let mut t = Activity::start(
+            "test project".to_string(),
+            "test description".to_string(),
+            None,
+        );
+        t.stop(None);
+        t.paused = true;
+
+        let line = format!("{t}");
+        assert!(line.ends_with(" | (paused)\n"));
+        let t2 = Activity::from_str(&line).unwrap();
+        assert!(t2.paused);
+        assert!(t2.tags.is_empty());
+
+        t.tags = vec!["meeting".to_string()];
+        let line = format!("{t}");
+        assert!(line.ends_with(" | +meeting (paused)\n"));
+        let t2 = Activity::from_str(&line).unwrap();
+        assert!(t2.paused);
+        assert_eq!(t2.tags, vec!["meeting".to_string()]);
+
+        t.resumed = true;
+        let line = format!("{t}");
+        assert!(line.ends_with(" | +meeting (resumed) (paused)\n"));
+        let t2 = Activity::from_str(&line).unwrap();
+        assert!(t2.paused && t2.resumed);
+        assert_eq!(t2.tags, vec!["meeting".to_string()]);
+
+        t.paused = false;
+        let t2 = Activity::from_str(&format!("{t}")).unwrap();
+        assert!(!t2.paused && t2.resumed);
+    }
+
+    #[test]
+    fn notes_roundtrip() {
+        let mut t = Activity::start(
+            "test project".to_string(),
+            "test description".to_string(),
+            None,
+        );
+        t.set_notes("see ticket #12 | done\nnext line");
+        assert_eq!(t.notes.as_deref(), Some("see ticket #12 | done next line"));
+
+        let line = format!("{t}");
+        assert!(line.ends_with(" | test description |  | see ticket #12 \\| done next line\n"));
+        let t2 = Activity::from_str(&line).unwrap();
+        assert_eq!(t2.notes, t.notes);
+        assert!(t2.tags.is_empty());
+
+        t.tags = vec!["meeting".to_string()];
+        let t2 = Activity::from_str(&format!("{t}")).unwrap();
+        assert_eq!(t2.notes, t.notes);
+        assert_eq!(t2.tags, t.tags);
+
+        t.set_notes(" ");
+        assert_eq!(t.notes, None);
+        assert!(format!("{t}").ends_with(" | +meeting\n"));
+        assert_eq!(
+            Activity::from_str("2021-02-16 16:14 | p | d |  | ")
+                .unwrap()
+                .notes,
+            None
+        );
+    }
+
+    #[test]
+    fn offset_roundtrip() {
+        let t = Activity::from_str("2024-10-27 02:30+01:00 - 2024-10-27 03:00 | p | d").unwrap();
+        assert_eq!(t.start.time().hour(), 2);
+        assert_eq!(t.start_offset, FixedOffset::east_opt(3600));
+        assert_eq!(t.end_offset, None);
+
+        let line = format!("{t}");
+        assert_eq!(line.matches("+01:00").count(), 1);
+        let t2 = Activity::from_str(&line).unwrap();
+        assert_eq!(t2.start, t.start);
+        assert_eq!(t2.start_offset, t.start_offset);
+        assert_eq!(t2.end_offset, None);
+
+        let t = Activity::from_str("2024-10-27 02:30-05:30 | p | d").unwrap();
+        assert_eq!(t.start_offset, FixedOffset::west_opt(5 * 3600 + 1800));
+
+        assert!(Activity::from_str("2024-10-27 02:30+1 | p | d").is_err());
+    }
+
+    #[test]
+    fn duration_across_transitions() {
+        let activity = |start, end| {
+            Activity::from_str(&format!("{start} - {end} | p | d"))
+                .unwrap()
+                .get_duration_in(&Cet2024)
+        };
+
+        // spring forward: the hour from 02:00 to 03:00 is skipped
+        assert_eq!(
+            activity("2024-03-31 01:00", "2024-03-31 04:00"),
+            Duration::hours(2)
+        );
+        // fall back: the hour from 02:00 to 03:00 is repeated
+        assert_eq!(
+            activity("2024-10-27 01:00", "2024-10-27 04:00"),
+            Duration::hours(4)
+        );
+        assert_eq!(
+            activity("2024-10-27 02:30", "2024-10-27 02:15+01:00"),
+            Duration::minutes(45)
+        );
+    }
+
+    #[test]
+    fn set_duration_across_transitions() {
+        let mut t = Activity::from_str("2024-10-27 02:30 | p | d").unwrap();
+        t.set_duration_in(&Cet2024, Duration::hours(1));
+
+        assert_eq!(t.end.unwrap().time().hour(), 2);
+        assert_eq!(t.end_offset, FixedOffset::east_opt(3600));
+        assert_eq!(t.get_duration_in(&Cet2024), Duration::hours(1));
+    }
+
+    #[test]
+    fn parse_tags_test() {
+        assert_eq!(
+            parse_tags(" +meeting review  +meeting +"),
+            vec!["meeting".to_string(), "review".to_string()]
+        );
+        assert!(parse_tags("").is_empty());
+    }
+
     #[test]
     fn from_str_errors() {
         let t = Activity::from_str("2021 test project");
@@ -1,10 +1,26 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime};
+use std::fs;
 use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::{BufRead, BufReader, Write};
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::data::activity;
+use crate::data::journal;
+
+static BACKUPS: AtomicUsize = AtomicUsize::new(0);
+
+// sets the number of backups that are kept of a bartib file, `conf::DEFAULT_BACKUPS` unless
+// configured otherwise. No backups are kept until it has been set
+pub fn set_backups(count: usize) {
+    BACKUPS.store(count, Ordering::Relaxed);
+}
 
 #[derive(Debug)]
 pub enum LineStatus {
@@ -12,6 +28,31 @@ pub enum LineStatus {
     Changed,
 }
 
+// the kind of content of a line in a bartib file
+#[derive(Debug, PartialEq, Eq)]
+pub enum LineKind {
+    // a line that is meant to contain an activity, even if it can not be parsed
+    Activity,
+    // a line starting with `#`
+    Comment,
+    // an empty line or a line with whitespace only
+    Blank,
+}
+
+impl LineKind {
+    // the kind of a line by its text
+    #[must_use]
+    pub fn of(plaintext: &str) -> Self {
+        if plaintext.trim().is_empty() {
+            Self::Blank
+        } else if plaintext.trim_start().starts_with('#') {
+            Self::Comment
+        } else {
+            Self::Activity
+        }
+    }
+}
+
 // a line in a bartib file
 #[derive(Debug)]
 pub struct Line {
@@ -20,7 +61,8 @@ pub struct Line {
     pub plaintext: Option<String>,
     // the line number
     pub line_number: Option<usize>,
-    // the result of parsing this line to a activity
+    pub kind: LineKind,
+    // the result of parsing this line to a activity. Comments and blank lines never contain one
     pub activity: Result<activity::Activity, activity::ActivityError>,
     // the status of this activity
     status: LineStatus,
@@ -30,10 +72,18 @@ impl Line {
     // creates a new line struct from plaintext
     #[must_use]
     pub fn new(plaintext: &str, line_number: usize) -> Self {
+        let kind = LineKind::of(plaintext);
+        let activity = match kind {
+            LineKind::Activity => activity::Activity::from_str(plaintext),
+            _ => Err(activity::ActivityError::NoActivity),
+        };
+
         Self {
-            plaintext: Some(plaintext.trim().to_string()),
+            // only the carriage return of windows line endings is removed
+            plaintext: Some(plaintext.trim_end_matches('\r').to_string()),
             line_number: Some(line_number),
-            activity: activity::Activity::from_str(plaintext),
+            kind,
+            activity,
             status: LineStatus::Unchanged,
         }
     }
@@ -44,58 +94,591 @@ impl Line {
         Self {
             plaintext: None,
             line_number: None,
+            kind: LineKind::Activity,
             activity: Ok(activity),
             status: LineStatus::Changed,
         }
     }
 
+    // whether the line should contain an activity but could not be parsed
+    #[must_use]
+    pub fn has_error(&self) -> bool {
+        self.kind == LineKind::Activity && self.activity.is_err()
+    }
+
     // sets the status of the line to changed
     pub fn set_changed(&mut self) {
         self.status = LineStatus::Changed;
     }
+
+    // the text of the line as it is written to the file
+    //
+    // unchanged lines keep their original text
+    fn get_text(&self) -> String {
+        match (&self.status, &self.plaintext) {
+            (LineStatus::Unchanged, Some(plaintext)) => plaintext.clone(),
+            _ => self
+                .activity
+                .as_ref()
+                .unwrap()
+                .to_string()
+                .trim_end_matches('\n')
+                .to_string(),
+        }
+    }
+}
+
+// the content of a bartib file
+#[derive(Debug, Default)]
+pub struct FileContent {
+    // the text of the file as it has been read. It is used to write back only the lines
+    // that have changed
+    text: String,
+    lines: Vec<Line>,
+}
+
+impl Deref for FileContent {
+    type Target = Vec<Line>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.lines
+    }
+}
+
+impl DerefMut for FileContent {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.lines
+    }
+}
+
+impl<'a> IntoIterator for &'a FileContent {
+    type Item = &'a Line;
+    type IntoIter = std::slice::Iter<'a, Line>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.lines.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut FileContent {
+    type Item = &'a mut Line;
+    type IntoIter = std::slice::IterMut<'a, Line>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.lines.iter_mut()
+    }
 }
 
 // reads the content of a file to a vector of lines
-pub fn get_file_content(file_name: &str) -> Result<Vec<Line>> {
-    let file_handler =
-        File::open(file_name).context(format!("Could not read from file: {file_name}"))?;
-    let reader = BufReader::new(file_handler);
+pub fn get_file_content(file_name: &str) -> Result<FileContent> {
+    let text =
+        fs::read_to_string(file_name).context(format!("Could not read from file: {file_name}"))?;
 
-    let lines = reader
+    let lines = text
         .lines()
-        .map_while(Result::ok)
         .enumerate()
-        .map(|(line_number, line)| Line::new(&line, line_number.saturating_add(1)))
+        .map(|(line_number, line)| Line::new(line, line_number.saturating_add(1)))
         .collect();
 
-    Ok(lines)
+    Ok(FileContent { text, lines })
+}
+
+// writes the lines of the content back into the file
+//
+// the change is recorded in the journal, so it can be undone later
+pub fn write_to_file(file_name: &str, file_content: &FileContent) -> Result<(), io::Error> {
+    let new_lines: Vec<String> = file_content.iter().map(Line::get_text).collect();
+    let previous_lines: Vec<String> = file_content.text.lines().map(str::to_string).collect();
+
+    write_changed_lines(
+        file_name,
+        &file_content.text,
+        &new_lines,
+        BACKUPS.load(Ordering::Relaxed),
+    )?;
+    journal::warn_on_error(journal::record(file_name, &previous_lines, &new_lines));
+
+    Ok(())
+}
+
+// reads the plain lines of a file
+pub fn read_lines(file_name: &str) -> Result<Vec<String>, io::Error> {
+    let content = fs::read_to_string(file_name)?;
+    Ok(content.lines().map(str::to_string).collect())
+}
+
+// writes plain lines into a file without recording the change in the journal
+pub fn write_lines(file_name: &str, lines: &[String]) -> Result<(), io::Error> {
+    let previous_content = read_content(file_name)?;
+    write_changed_lines(
+        file_name,
+        &previous_content,
+        lines,
+        BACKUPS.load(Ordering::Relaxed),
+    )
+}
+
+// writes plain lines into a file without recording the change in the journal and without
+// creating a backup. Used when restoring a backup, as a new one would shift the numbers of the
+// existing backups
+pub fn write_lines_without_backup(file_name: &str, lines: &[String]) -> Result<(), io::Error> {
+    let previous_content = read_content(file_name)?;
+    write_changed_lines(file_name, &previous_content, lines, 0)
+}
+
+// reads the content of a file. The file does not exist yet, when the first activity is started
+fn read_content(file_name: &str) -> Result<String, io::Error> {
+    match fs::read_to_string(file_name) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e),
+    }
+}
+
+// writes the lines that differ from the previous content of the file
+//
+// new activities are appended to the file. Any other change is written to a temporary file
+// which then replaces the original, so the file is never left half written. Before the file is
+// replaced, its previous content is kept in a new backup, as long as `backups` is not 0.
+fn write_changed_lines(
+    file_name: &str,
+    previous_content: &str,
+    lines: &[String],
+    backups: usize,
+) -> Result<(), io::Error> {
+    let unchanged_lines = previous_content
+        .lines()
+        .zip(lines)
+        .take_while(|(previous, line)| previous == line)
+        .count();
+    let offset = get_line_offset(previous_content, unchanged_lines);
+
+    let mut tail = String::new();
+    // the last line of the file might not have been terminated
+    if offset > 0 && !previous_content[..offset].ends_with('\n') {
+        tail.push('\n');
+    }
+    for line in &lines[unchanged_lines..] {
+        tail.push_str(line);
+        tail.push('\n');
+    }
+
+    if offset == previous_content.len() && tail.is_empty() {
+        return Ok(());
+    }
+
+    if offset == previous_content.len() {
+        let mut file_handler = get_bartib_file_appendable(file_name)?;
+        file_handler.write_all(tail.as_bytes())?;
+        file_handler.sync_all()
+    } else {
+        create_backup(file_name, previous_content.as_bytes(), backups)?;
+
+        let content = previous_content[..offset].to_string() + &tail;
+        replace_file(file_name, content.as_bytes())
+    }
+}
+
+// replaces the content of a file by writing it to a temporary file which is renamed afterwards
+fn replace_file(file_name: &str, content: &[u8]) -> Result<(), io::Error> {
+    // a symlink is kept and the file it points to is replaced
+    let path = fs::canonicalize(file_name).unwrap_or_else(|_| PathBuf::from(file_name));
+    let temp_path = get_temp_path(&path);
+
+    let result =
+        write_temp_file(&path, &temp_path, content).and_then(|()| fs::rename(&temp_path, &path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+
+    sync_directory(&path)
+}
+
+fn write_temp_file(path: &Path, temp_path: &Path, content: &[u8]) -> Result<(), io::Error> {
+    let mut file_handler = File::create(temp_path)?;
+    file_handler.write_all(content)?;
+    file_handler.sync_all()?;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp_path, metadata.permissions())?;
+    }
+
+    Ok(())
+}
+
+// the temporary file is created next to the file, as renaming only works within a file system
+fn get_temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(file_name)
+}
+
+// makes sure the renaming of a file survives a crash
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> Result<(), io::Error> {
+    match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => File::open(directory)?.sync_all(),
+        _ => File::open(".")?.sync_all(),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> Result<(), io::Error> {
+    Ok(())
+}
+
+// copies a file to be edited next to it and returns the path of the copy. An empty copy is
+// created if the file does not exist yet
+pub fn create_copy(file_name: &str) -> Result<PathBuf, io::Error> {
+    let mut copy_name = PathBuf::from(file_name).into_os_string();
+    copy_name.push(format!(".{}.edit", std::process::id()));
+    let copy_path = PathBuf::from(copy_name);
+
+    match fs::copy(file_name, &copy_path) {
+        Ok(_) => Ok(copy_path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            File::create(&copy_path)?;
+            Ok(copy_path)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// a copy of a bartib file from before it has been changed
+#[derive(Debug)]
+pub struct Backup {
+    // the number of the backup, starting with 1 for the latest one
+    pub number: usize,
+    pub path: PathBuf,
+    // the time the backup has been created
+    pub time: Option<NaiveDateTime>,
+}
+
+// stores the content of a file in a new backup, keeping at most `count` backups
+//
+// backups are named like the file with the suffix `.bak.1` for the latest backup, `.bak.2` for
+// the one before and so on
+fn create_backup(file_name: &str, content: &[u8], count: usize) -> Result<(), io::Error> {
+    if count == 0 || content.is_empty() {
+        return Ok(());
+    }
+
+    for backup in get_backups(file_name).iter().rev() {
+        if backup.number >= count {
+            fs::remove_file(&backup.path)?;
+        } else {
+            fs::rename(&backup.path, get_backup_path(file_name, backup.number + 1))?;
+        }
+    }
+
+    fs::write(get_backup_path(file_name, 1), content)
+}
+
+// the existing backups of a file, starting with the latest one
+#[must_use]
+pub fn get_backups(file_name: &str) -> Vec<Backup> {
+    let mut backups = Vec::new();
+    let mut number = 1;
+
+    loop {
+        let path = get_backup_path(file_name, number);
+        let Ok(metadata) = fs::metadata(&path) else {
+            break;
+        };
+
+        let time = metadata
+            .modified()
+            .ok()
+            .map(|time| DateTime::<Local>::from(time).naive_local());
+        backups.push(Backup { number, path, time });
+        number += 1;
+    }
+
+    backups
+}
+
+#[must_use]
+pub fn get_backup_path(file_name: &str, number: usize) -> PathBuf {
+    PathBuf::from(format!("{file_name}.bak.{number}"))
+}
+
+// the byte offset at which a line begins. Lines behind the end of the content begin at its end
+fn get_line_offset(content: &str, line_index: usize) -> usize {
+    if line_index == 0 {
+        return 0;
+    }
+
+    content
+        .match_indices('\n')
+        .nth(line_index - 1)
+        .map_or(content.len(), |(index, _)| index + 1)
+}
+
+// the kind of lock held on a bartib file
+pub enum LockKind {
+    // for reading the file. Several processes may read the file at the same time
+    Shared,
+    // for changing the file. No other process may read or write the file meanwhile
+    Exclusive,
+}
+
+// an advisory lock on a bartib file which is released when dropped
+//
+// the lock is held on a separate lock file next to the bartib file, as writing the bartib file
+// may replace it by a new file
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
 }
 
-// writes a vector of lines into a file
-pub fn write_to_file(file_name: &str, file_content: &[Line]) -> Result<(), io::Error> {
-    let file_handler = get_bartib_file_writable(file_name)?;
+// locks a bartib file, waiting at most `timeout` for other processes to release their locks
+//
+// only exclusive locks create the lock file. A shared lock fails with `NotFound` if the file has
+// never been changed by bartib
+pub fn lock(file_name: &str, kind: &LockKind, timeout: Duration) -> Result<FileLock, io::Error> {
+    let lock_file_name = format!("{file_name}.lock");
+    let file = match kind {
+        LockKind::Shared => File::open(lock_file_name)?,
+        LockKind::Exclusive => OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(lock_file_name)?,
+    };
+    let started = Instant::now();
 
-    for line in file_content {
-        match &line.status {
-            LineStatus::Unchanged => {
-                if let Some(plaintext) = &line.plaintext {
-                    writeln!(&file_handler, "{plaintext}")?
-                } else {
-                    write!(&file_handler, "{}", line.activity.as_ref().unwrap())?
-                }
+    loop {
+        let result = match kind {
+            LockKind::Shared => file.try_lock_shared(),
+            LockKind::Exclusive => file.try_lock(),
+        };
+
+        match result {
+            Ok(()) => return Ok(FileLock { _file: file }),
+            Err(fs::TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(fs::TryLockError::WouldBlock) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "the file is locked by another process",
+                ))
             }
-            LineStatus::Changed => write!(&file_handler, "{}", line.activity.as_ref().unwrap())?,
+            Err(fs::TryLockError::Error(e)) => return Err(e),
         }
     }
+}
 
-    Ok(())
+// create a handle to append to a file
+fn get_bartib_file_appendable(file_name: &str) -> Result<File, io::Error> {
+    OpenOptions::new().create(true).append(true).open(file_name)
 }
 
-// create a write handle to a file
-fn get_bartib_file_writable(file_name: &str) -> Result<File, io::Error> {
-    OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(file_name)
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    // a bartib file in the temporary directory for tests. The file and the files bartib creates
+    // next to it are removed when it is dropped
+    pub struct TestFile {
+        pub name: String,
+    }
+
+    impl TestFile {
+        pub fn new(name: &str) -> Self {
+            let name = std::env::temp_dir()
+                .join(format!("bartib_test_{name}_{}", std::process::id()))
+                .to_string_lossy()
+                .to_string();
+            Self { name }
+        }
+    }
+
+    impl Drop for TestFile {
+        fn drop(&mut self) {
+            for backup in get_backups(&self.name) {
+                let _ = fs::remove_file(backup.path);
+            }
+
+            for suffix in ["", ".lock", ".undo", ".redo"] {
+                let _ = fs::remove_file(format!("{}{suffix}", self.name));
+            }
+        }
+    }
+
+    fn write_and_read(name: &str, previous_content: &str, lines: &[&str]) -> String {
+        let file = TestFile::new(&format!("file_{name}"));
+        let lines: Vec<String> = lines.iter().map(|l| (*l).to_string()).collect();
+
+        fs::write(&file.name, previous_content).unwrap();
+        write_lines(&file.name, &lines).unwrap();
+        fs::read_to_string(&file.name).unwrap()
+    }
+
+    #[test]
+    fn line_kinds() {
+        let activity = Line::new("2024-03-01 09:00 | p | d", 1);
+        assert_eq!(activity.kind, LineKind::Activity);
+        assert!(activity.activity.is_ok());
+
+        let invalid = Line::new("2024-03-01 | p | d", 2);
+        assert_eq!(invalid.kind, LineKind::Activity);
+        assert!(invalid.has_error());
+
+        let comment = Line::new("  # 2024-03-01 09:00 | p | d", 3);
+        assert_eq!(comment.kind, LineKind::Comment);
+        assert!(comment.activity.is_err());
+        assert!(!comment.has_error());
+        assert_eq!(comment.get_text(), "  # 2024-03-01 09:00 | p | d");
+
+        let blank = Line::new(" \r", 4);
+        assert_eq!(blank.kind, LineKind::Blank);
+        assert!(!blank.has_error());
+    }
+
+    #[test]
+    fn write_appended_lines() {
+        assert_eq!(
+            write_and_read("append", "a\nb\n", &["a", "b", "c"]),
+            "a\nb\nc\n"
+        );
+        assert_eq!(write_and_read("append_empty", "", &["a"]), "a\n");
+        // the last line had not been terminated
+        assert_eq!(
+            write_and_read("append_unterminated", "a\nb", &["a", "b", "c"]),
+            "a\nb\nc\n"
+        );
+    }
+
+    #[test]
+    fn write_changed_lines() {
+        assert_eq!(
+            write_and_read("change", "a\nb\nc\n", &["a", "x", "c"]),
+            "a\nx\nc\n"
+        );
+        assert_eq!(write_and_read("remove", "a\nb\nc\n", &["a", "b"]), "a\nb\n");
+        assert_eq!(write_and_read("first", "a\nb\n", &["x", "b"]), "x\nb\n");
+        assert_eq!(write_and_read("unchanged", "a\nb\n", &["a", "b"]), "a\nb\n");
+        // unchanged lines keep their line endings
+        assert_eq!(
+            write_and_read("crlf", "a\r\nb\r\n", &["a", "x"]),
+            "a\r\nx\n"
+        );
+    }
+
+    #[test]
+    fn write_file_content() {
+        let file = TestFile::new("file_content");
+        let file_name = &file.name;
+        fs::write(
+            file_name,
+            "# comment\r\n2024-03-01 09:00 - 2024-03-01 10:00 | p | a\n2024-03-01 11:00 | p | b\n",
+        )
+        .unwrap();
+
+        let mut file_content = get_file_content(file_name).unwrap();
+        assert_eq!(file_content.len(), 3);
+        file_content.remove(1);
+        write_to_file(file_name, &file_content).unwrap();
+
+        let content = fs::read_to_string(file_name).unwrap();
+        let undo = fs::read_to_string(format!("{file_name}.undo")).unwrap();
+
+        assert_eq!(content, "# comment\r\n2024-03-01 11:00 | p | b\n");
+        assert!(undo.contains("2024-03-01 09:00 - 2024-03-01 10:00 | p | a"));
+    }
+
+    #[test]
+    fn write_file_content_with_broken_journal() {
+        let file = TestFile::new("file_journal");
+        let file_name = &file.name;
+        fs::write(file_name, "2024-03-01 11:00 | p | b\n").unwrap();
+        fs::write(format!("{file_name}.undo"), "not a journal\n").unwrap();
+
+        let mut file_content = get_file_content(file_name).unwrap();
+        file_content.clear();
+        let result = write_to_file(file_name, &file_content);
+
+        let content = fs::read_to_string(file_name).unwrap();
+        let undo = fs::read_to_string(format!("{file_name}.undo")).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(content, "");
+        // the broken journal is left untouched
+        assert_eq!(undo, "not a journal\n");
+    }
+
+    #[test]
+    fn line_offset() {
+        assert_eq!(get_line_offset("ab\ncd\n", 0), 0);
+        assert_eq!(get_line_offset("ab\ncd\n", 1), 3);
+        assert_eq!(get_line_offset("ab\ncd\n", 2), 6);
+        assert_eq!(get_line_offset("ab\ncd", 2), 5);
+    }
+
+    #[test]
+    fn backup_replaced_file() {
+        let file = TestFile::new("file_replace");
+        let file_name = &file.name;
+        let lines = |l: &[&str]| l.iter().map(|s| (*s).to_string()).collect::<Vec<String>>();
+
+        // appending lines does not need a backup
+        fs::write(file_name, "a\n").unwrap();
+        super::write_changed_lines(file_name, "a\n", &lines(&["a", "b"]), 3).unwrap();
+        assert!(get_backups(file_name).is_empty());
+
+        super::write_changed_lines(file_name, "a\nb\n", &lines(&["x", "b"]), 3).unwrap();
+        let backups = get_backups(file_name);
+        let backup = fs::read_to_string(&backups[0].path).unwrap();
+
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backup, "a\nb\n");
+    }
+
+    #[test]
+    fn rotate_backups() {
+        let file = TestFile::new("file_backup");
+        let file_name = &file.name;
+
+        for content in ["a\n", "b\n", "c\n", "d\n"] {
+            create_backup(file_name, content.as_bytes(), 3).unwrap();
+        }
+
+        let backups = get_backups(file_name);
+        let contents: Vec<String> = backups
+            .iter()
+            .map(|backup| fs::read_to_string(&backup.path).unwrap())
+            .collect();
+        assert_eq!(contents, vec!["d\n", "c\n", "b\n"]);
+        assert_eq!(backups[2].number, 3);
+
+        // lowering the number of backups removes the oldest ones
+        create_backup(file_name, b"e\n", 2).unwrap();
+        assert_eq!(get_backups(file_name).len(), 2);
+    }
+
+    #[test]
+    fn lock_file() {
+        let file = TestFile::new("file_lock");
+        let file_name = &file.name;
+        let timeout = Duration::from_millis(100);
+
+        // shared locks do not create the lock file
+        let error = lock(file_name, &LockKind::Shared, timeout).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(!Path::new(&format!("{file_name}.lock")).exists());
+
+        drop(lock(file_name, &LockKind::Exclusive, timeout).unwrap());
+        let shared = lock(file_name, &LockKind::Shared, timeout).unwrap();
+        assert!(lock(file_name, &LockKind::Shared, timeout).is_ok());
+        let error = lock(file_name, &LockKind::Exclusive, timeout).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+
+        drop(shared);
+        let exclusive = lock(file_name, &LockKind::Exclusive, timeout).unwrap();
+        assert!(lock(file_name, &LockKind::Shared, timeout).is_err());
+
+        drop(exclusive);
+    }
 }
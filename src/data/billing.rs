@@ -0,0 +1,86 @@
+use wildmatch::WildMatch;
+
+use crate::conf::HourlyRate;
+use crate::data::activity::Activity;
+
+// the hourly rate of a project
+//
+// the first rate whose pattern matches the project is used
+#[must_use]
+pub fn get_hourly_rate(rates: &[HourlyRate], project: &str) -> Option<f64> {
+    rates
+        .iter()
+        .find(|rate| WildMatch::new(&rate.project).matches(project))
+        .map(|rate| rate.rate)
+}
+
+// the billable amount of activities
+//
+// activities of projects without a rate are not billed. Returns None if none of the activities
+// is billed
+#[must_use]
+pub fn get_amount(rates: &[HourlyRate], activities: &[&Activity]) -> Option<f64> {
+    activities
+        .iter()
+        .filter_map(|activity| {
+            let hours = activity.get_duration().num_seconds() as f64 / 3600.0;
+            get_hourly_rate(rates, &activity.project).map(|rate| rate * hours)
+        })
+        .reduce(|a, b| a + b)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+
+    use super::*;
+
+    fn rates() -> Vec<HourlyRate> {
+        vec![
+            HourlyRate {
+                project: "client-*".to_string(),
+                rate: 90.0,
+            },
+            HourlyRate {
+                project: "client-a".to_string(),
+                rate: 10.0,
+            },
+            HourlyRate {
+                project: "internal".to_string(),
+                rate: 50.0,
+            },
+        ]
+    }
+
+    fn activity(project: &str, minutes: i64) -> Activity {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let mut a = Activity::start(project.to_string(), "d".to_string(), Some(start));
+        a.end = Some(start + Duration::minutes(minutes));
+        a
+    }
+
+    #[test]
+    fn get_hourly_rate_test() {
+        let rates = rates();
+
+        // the first matching rate wins
+        assert_eq!(get_hourly_rate(&rates, "client-a"), Some(90.0));
+        assert_eq!(get_hourly_rate(&rates, "internal"), Some(50.0));
+        assert_eq!(get_hourly_rate(&rates, "other"), None);
+    }
+
+    #[test]
+    fn get_amount_test() {
+        let rates = rates();
+        let a1 = activity("client-a", 90);
+        let a2 = activity("internal", 30);
+        let a3 = activity("other", 60);
+
+        assert_eq!(get_amount(&rates, &[&a1, &a2, &a3]), Some(160.0));
+        assert_eq!(get_amount(&rates, &[&a3]), None);
+        assert_eq!(get_amount(&rates, &[]), None);
+    }
+}
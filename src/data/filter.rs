@@ -1,6 +1,6 @@
 use crate::data::activity::Activity;
 
-use chrono::{Datelike, Duration, NaiveDate};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
 
 pub struct Filters {}
 
@@ -11,8 +11,8 @@ impl Filters {
     pub fn today(today: NaiveDate) -> impl Fn(&&Activity) -> bool {
         move |activity: &&Activity| activity.start.date() == today
     }
-    pub fn current_week(today: NaiveDate) -> impl Fn(&&Activity) -> bool {
-        let from_date = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    pub fn current_week(today: NaiveDate, week_start: Weekday) -> impl Fn(&&Activity) -> bool {
+        let from_date = first_day_of_week(today, week_start);
         let to_date = today;
         move |activity: &&Activity| {
             activity.start.date() >= from_date && activity.start.date() <= to_date
@@ -27,6 +27,122 @@ impl Filters {
     }
 }
 
+// the first day of the week that contains the given date
+#[must_use]
+pub fn first_day_of_week(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    let days_since_week_start =
+        (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    date - Duration::days(i64::from(days_since_week_start))
+}
+
+// the first and the last day of a named date range like `current_week`
+#[must_use]
+pub fn get_named_range(
+    name: &str,
+    today: NaiveDate,
+    week_start: Weekday,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let first_day_of_week = first_day_of_week(today, week_start);
+
+    match name {
+        "today" => Some((today, today)),
+        "yesterday" => today.pred_opt().map(|yesterday| (yesterday, yesterday)),
+        "current_week" => Some((first_day_of_week, first_day_of_week + Duration::days(6))),
+        "last_week" => Some((
+            first_day_of_week - Duration::weeks(1),
+            first_day_of_week - Duration::days(1),
+        )),
+        "current_month" => get_month_range(today.year(), today.month()),
+        "last_month" => {
+            let last_day = today.with_day(1)?.pred_opt()?;
+            get_month_range(last_day.year(), last_day.month())
+        }
+        "current_year" => Some((
+            NaiveDate::from_ymd_opt(today.year(), 1, 1)?,
+            NaiveDate::from_ymd_opt(today.year(), 12, 31)?,
+        )),
+        _ => None,
+    }
+}
+
+// the first and the last day of a month
+#[must_use]
+pub fn get_month_range(year: i32, month: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+
+    Some((first_day, next_month.pred_opt()?))
+}
+
+// the first and the last day of a quarter, counted from 1 to 4
+#[must_use]
+pub fn get_quarter_range(year: i32, quarter: u32) -> Option<(NaiveDate, NaiveDate)> {
+    if !(1..=4).contains(&quarter) {
+        return None;
+    }
+
+    let (first_day, _) = get_month_range(year, quarter * 3 - 2)?;
+    let (_, last_day) = get_month_range(year, quarter * 3)?;
+    Some((first_day, last_day))
+}
+
+// the range of the given number of days up to and including today
+#[must_use]
+pub fn get_last_days_range(today: NaiveDate, days: u32) -> Option<(NaiveDate, NaiveDate)> {
+    if days == 0 {
+        return None;
+    }
+
+    Some((today - Duration::days(i64::from(days - 1)), today))
+}
+
+// parses a month like `2024-03`
+#[must_use]
+pub fn parse_month(month: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let first_day = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d").ok()?;
+    get_month_range(first_day.year(), first_day.month())
+}
+
+// parses a week like `12` for the current year or `2024-W12`
+//
+// weeks are numbered like ISO weeks. If the week starts on another day than monday, the week
+// containing the monday of the ISO week is selected
+#[must_use]
+pub fn parse_week(
+    week: &str,
+    today: NaiveDate,
+    week_start: Weekday,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let week = week.trim().to_uppercase();
+
+    let (year, week) = match week.split_once("-W") {
+        Some((year, week)) => (year.parse().ok()?, week.parse().ok()?),
+        None => (
+            today.iso_week().year(),
+            week.trim_start_matches('W').parse().ok()?,
+        ),
+    };
+
+    let monday = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+    let first_day = first_day_of_week(monday, week_start);
+    Some((first_day, first_day + Duration::days(6)))
+}
+
+// parses a quarter like `2` for the current year or `2024-Q2`
+#[must_use]
+pub fn parse_quarter(quarter: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let quarter = quarter.trim().to_uppercase();
+
+    match quarter.split_once("-Q") {
+        Some((year, quarter)) => get_quarter_range(year.parse().ok()?, quarter.parse().ok()?),
+        None => get_quarter_range(today.year(), quarter.trim_start_matches('Q').parse().ok()?),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::NaiveDateTime;
@@ -61,10 +177,33 @@ mod tests {
         let activities = data();
         let res: Vec<&Activity> = activities
             .iter()
-            .filter(Filters::current_week(now.date()))
+            .filter(Filters::current_week(now.date(), Weekday::Mon))
             .collect();
         assert_eq!(res.len(), 3);
         assert_eq!(res.first().unwrap().description.as_str(), "d2");
+
+        // the week of 2024-03-19 starts on tuesday
+        let res: Vec<&Activity> = activities
+            .iter()
+            .filter(Filters::current_week(now.date(), Weekday::Tue))
+            .collect();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res.first().unwrap().description.as_str(), "d3");
+    }
+
+    #[test]
+    fn first_day_of_week_test() {
+        let tuesday = NaiveDate::from_ymd_opt(2024, 3, 19).unwrap();
+
+        assert_eq!(
+            first_day_of_week(tuesday, Weekday::Mon),
+            NaiveDate::from_ymd_opt(2024, 3, 18).unwrap()
+        );
+        assert_eq!(first_day_of_week(tuesday, Weekday::Tue), tuesday);
+        assert_eq!(
+            first_day_of_week(tuesday, Weekday::Sun),
+            NaiveDate::from_ymd_opt(2024, 3, 17).unwrap()
+        );
     }
 
     #[test]
@@ -79,36 +218,172 @@ mod tests {
         assert_eq!(res.first().unwrap().description.as_str(), "d1");
     }
 
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn named_ranges() {
+        let today = day(2024, 3, 19);
+        let range = |name| get_named_range(name, today, Weekday::Mon);
+
+        assert_eq!(range("today"), Some((today, today)));
+        assert_eq!(
+            range("yesterday"),
+            Some((day(2024, 3, 18), day(2024, 3, 18)))
+        );
+        assert_eq!(
+            range("current_week"),
+            Some((day(2024, 3, 18), day(2024, 3, 24)))
+        );
+        assert_eq!(
+            range("last_week"),
+            Some((day(2024, 3, 11), day(2024, 3, 17)))
+        );
+        assert_eq!(
+            range("current_month"),
+            Some((day(2024, 3, 1), day(2024, 3, 31)))
+        );
+        assert_eq!(
+            range("last_month"),
+            Some((day(2024, 2, 1), day(2024, 2, 29)))
+        );
+        assert_eq!(
+            range("current_year"),
+            Some((day(2024, 1, 1), day(2024, 12, 31)))
+        );
+        assert_eq!(range("forever"), None);
+
+        assert_eq!(
+            get_named_range("last_month", day(2024, 1, 10), Weekday::Mon),
+            Some((day(2023, 12, 1), day(2023, 12, 31)))
+        );
+    }
+
+    #[test]
+    fn months_and_quarters() {
+        let today = day(2024, 3, 19);
+
+        assert_eq!(
+            parse_month("2024-12"),
+            Some((day(2024, 12, 1), day(2024, 12, 31)))
+        );
+        assert_eq!(
+            parse_month("2023-02"),
+            Some((day(2023, 2, 1), day(2023, 2, 28)))
+        );
+        assert_eq!(parse_month("2024-13"), None);
+        assert_eq!(parse_month("march"), None);
+
+        assert_eq!(
+            parse_quarter("2", today),
+            Some((day(2024, 4, 1), day(2024, 6, 30)))
+        );
+        assert_eq!(
+            parse_quarter("q4", today),
+            Some((day(2024, 10, 1), day(2024, 12, 31)))
+        );
+        assert_eq!(
+            parse_quarter("2023-Q1", today),
+            Some((day(2023, 1, 1), day(2023, 3, 31)))
+        );
+        assert_eq!(parse_quarter("5", today), None);
+        assert_eq!(parse_quarter("2023-Q0", today), None);
+    }
+
+    #[test]
+    fn weeks() {
+        let today = day(2024, 3, 19);
+
+        assert_eq!(
+            parse_week("2024-W12", today, Weekday::Mon),
+            Some((day(2024, 3, 18), day(2024, 3, 24)))
+        );
+        assert_eq!(
+            parse_week("w1", today, Weekday::Mon),
+            Some((day(2024, 1, 1), day(2024, 1, 7)))
+        );
+        // the first iso week of 2021 starts on 2021-01-04
+        assert_eq!(
+            parse_week("2021-W01", today, Weekday::Sun),
+            Some((day(2021, 1, 3), day(2021, 1, 9)))
+        );
+        assert_eq!(parse_week("2024-W54", today, Weekday::Mon), None);
+        assert_eq!(parse_week("2024-12", today, Weekday::Mon), None);
+    }
+
+    #[test]
+    fn last_days() {
+        let today = day(2024, 3, 19);
+
+        assert_eq!(get_last_days_range(today, 1), Some((today, today)));
+        assert_eq!(
+            get_last_days_range(today, 30),
+            Some((day(2024, 2, 19), today))
+        );
+        assert_eq!(get_last_days_range(today, 0), None);
+    }
+
     fn data() -> Vec<Activity> {
         let a0 = activity::Activity {
             project: "p1".to_string(),
             description: "d0".to_string(),
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
             start: date(2024, 2, 11),
             end: Some(date(2024, 2, 11) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
         };
         let a1 = activity::Activity {
             project: "p1".to_string(),
             description: "d1".to_string(),
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
             start: date(2024, 3, 11),
             end: Some(date(2024, 3, 11) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
         };
         let a2 = activity::Activity {
             project: "p1".to_string(),
             description: "d2".to_string(),
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
             start: date(2024, 3, 18),
             end: Some(date(2024, 3, 18) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
         };
         let a3 = activity::Activity {
             project: "p1".to_string(),
             description: "d3".to_string(),
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
             start: date(2024, 3, 19),
             end: Some(date(2024, 3, 19) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
         };
         let a4 = activity::Activity {
             project: "p1".to_string(),
             description: "d4".to_string(),
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
             start: date(2024, 3, 19),
             end: None,
+            start_offset: None,
+            end_offset: None,
         };
         vec![a0, a1, a2, a3, a4]
     }
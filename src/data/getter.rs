@@ -13,6 +13,7 @@ pub struct ActivityFilter<'a> {
     pub to_date: Option<NaiveDate>,
     pub date: Option<NaiveDate>,
     pub project: Option<&'a str>,
+    pub tag: Option<&'a str>,
 }
 
 #[must_use]
@@ -59,6 +60,32 @@ pub fn get_running_activities(file_content: &[bartib_file::Line]) -> Vec<&activi
         .collect()
 }
 
+#[must_use]
+pub fn get_paused_activities(file_content: &[bartib_file::Line]) -> Vec<&activity::Activity> {
+    filter_paused_activities(&get_activities(file_content).collect::<Vec<_>>())
+}
+
+// returns the activities which are still paused
+//
+// a paused activity keeps its marker in the file. It is no longer paused as soon as it has been
+// resumed or another activity has been started after the pause
+#[must_use]
+pub fn filter_paused_activities<'a>(
+    activities: &[&'a activity::Activity],
+) -> Vec<&'a activity::Activity> {
+    activities
+        .iter()
+        .filter(|activity| {
+            activity.paused
+                && !activities.iter().any(|other| {
+                    !std::ptr::eq(*other, **activity)
+                        && activity.end.is_some_and(|end| other.start >= end)
+                })
+        })
+        .copied()
+        .collect()
+}
+
 pub fn get_activities(
     file_content: &[bartib_file::Line],
 ) -> impl Iterator<Item = &activity::Activity> {
@@ -66,8 +93,10 @@ pub fn get_activities(
         .iter()
         .filter_map(|line: &bartib_file::Line| match &line.activity {
             Ok(activity) => Some(activity),
+            Err(_) if !line.has_error() => None,
             Err(_) => {
-                println!(
+                // warnings go to stderr so they do not end up in exported data
+                eprintln!(
                     "Warning: Ignoring line {}. Please see `bartib check` for further information",
                     line.line_number.unwrap_or(0),
                 );
@@ -76,31 +105,106 @@ pub fn get_activities(
         })
 }
 
+impl ActivityFilter<'_> {
+    // checks whether an activity passes all the criteria of this filter
+    #[must_use]
+    pub fn matches(&self, activity: &activity::Activity) -> bool {
+        let (from_date, to_date) = self.get_date_range();
+
+        activity.start.date() >= from_date
+            && activity.start.date() <= to_date
+            && self.matches_project_and_tag(activity)
+    }
+
+    // trims an activity to the date range of this filter
+    //
+    // contrary to `matches` an activity that started before the range but ends within it is
+    // accepted as well. Returns None if the activity does not overlap with the range or does
+    // not pass the other criteria of this filter.
+    #[must_use]
+    pub fn clip(&self, activity: &activity::Activity) -> Option<activity::Activity> {
+        if !self.matches_project_and_tag(activity) {
+            return None;
+        }
+
+        let (from_date, to_date) = self.get_date_range();
+        let range_start = from_date.and_hms_opt(0, 0, 0)?;
+        // there is no end of the range if it lasts until the last representable day
+        let range_end = to_date.succ_opt().and_then(|d| d.and_hms_opt(0, 0, 0));
+        let end = activity.get_end_or_now();
+
+        if range_end.is_some_and(|range_end| activity.start >= range_end)
+            || (activity.start < range_start && end <= range_start)
+        {
+            return None;
+        }
+
+        let mut clipped = activity.clone();
+        if activity.start < range_start {
+            clipped.move_start(range_start);
+        }
+        if let Some(range_end) = range_end.filter(|range_end| end > *range_end) {
+            clipped.move_end(range_end);
+        }
+
+        Some(clipped)
+    }
+
+    // the first and the last day of the date range of this filter
+    fn get_date_range(&self) -> (NaiveDate, NaiveDate) {
+        match self.date {
+            Some(date) => (date, date),
+            None => (
+                self.from_date.unwrap_or(NaiveDate::MIN),
+                self.to_date.unwrap_or(NaiveDate::MAX),
+            ),
+        }
+    }
+
+    fn matches_project_and_tag(&self, activity: &activity::Activity) -> bool {
+        self.project
+            .is_none_or(|p| WildMatch::new(p).matches(&activity.project))
+            && self.tag.is_none_or(|t| {
+                let tag_match = WildMatch::new(t.trim_start_matches('+'));
+                activity.tags.iter().any(|tag| tag_match.matches(tag))
+            })
+    }
+}
+
 pub fn filter_activities<'a>(
     activities: Vec<&'a activity::Activity>,
     filter: &'a ActivityFilter,
 ) -> Vec<&'a activity::Activity> {
-    let from_date: NaiveDate;
-    let to_date: NaiveDate;
-
-    if let Some(date) = filter.date {
-        from_date = date;
-        to_date = date;
-    } else {
-        from_date = filter.from_date.unwrap_or(NaiveDate::MIN);
-        to_date = filter.to_date.unwrap_or(NaiveDate::MAX);
-    }
+    activities
+        .into_iter()
+        .filter(move |activity| filter.matches(activity))
+        .collect()
+}
 
+// trims all activities to the date range of the filter
+//
+// activities which do not overlap with the range or do not pass the filter are omitted
+#[must_use]
+pub fn clip_activities(
+    activities: Vec<&activity::Activity>,
+    filter: &ActivityFilter,
+) -> Vec<activity::Activity> {
     activities
         .into_iter()
-        .filter(move |activity| {
-            activity.start.date() >= from_date && activity.start.date() <= to_date
-        })
-        .filter(move |activity| {
-            filter
-                .project
-                .is_none_or(|p| WildMatch::new(p).matches(&activity.project))
-        })
+        .filter_map(|activity| filter.clip(activity))
+        .collect()
+}
+
+// returns all activities that overlap with the time span of the given activity
+//
+// running activities are considered to last until now
+#[must_use]
+pub fn get_overlapping_activities<'a>(
+    file_content: &'a [bartib_file::Line],
+    activity: &activity::Activity,
+) -> Vec<&'a activity::Activity> {
+    get_activities(file_content)
+        .filter(|other| activity.overlaps(other))
         .collect()
 }
 
@@ -124,6 +228,8 @@ pub fn get_last_activity_by_start(
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
 
     #[test]
@@ -167,4 +273,150 @@ mod tests {
             (&"d1".to_string(), &"p1".to_string())
         );
     }
+
+    #[test]
+    fn filter_activities_by_tag() {
+        let mut a1 = activity::Activity::start("p1".to_string(), "d1".to_string(), None);
+        a1.tags = vec!["meeting".to_string()];
+        let mut a2 = activity::Activity::start("p1".to_string(), "d2".to_string(), None);
+        a2.tags = vec!["review".to_string(), "meeting-prep".to_string()];
+        let a3 = activity::Activity::start("p2".to_string(), "d3".to_string(), None);
+
+        let mut filter = ActivityFilter {
+            number_of_activities: None,
+            from_date: None,
+            to_date: None,
+            date: None,
+            project: None,
+            tag: Some("+meeting"),
+        };
+
+        let filtered = filter_activities(vec![&a1, &a2, &a3], &filter);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].description, "d1");
+
+        filter.tag = Some("meeting*");
+        let filtered = filter_activities(vec![&a1, &a2, &a3], &filter);
+        assert_eq!(filtered.len(), 2);
+
+        filter.tag = None;
+        let filtered = filter_activities(vec![&a1, &a2, &a3], &filter);
+        assert_eq!(filtered.len(), 3);
+    }
+
+    #[test]
+    fn clip_activities_test() {
+        let activities: Vec<activity::Activity> = [
+            "2024-02-29 23:00 - 2024-03-01 01:00 | p1 | before",
+            "2024-03-15 09:00 - 2024-03-15 10:00 | p1 | within",
+            "2024-03-31 22:00 - 2024-04-01 02:00 | p1 | after",
+            "2024-02-29 09:00 - 2024-03-01 00:00 | p1 | ends at start",
+            "2024-04-01 00:00 - 2024-04-01 01:00 | p1 | starts at end",
+            "2024-03-15 09:00 - 2024-03-15 10:00 | p2 | other project",
+        ]
+        .iter()
+        .map(|line| activity::Activity::from_str(line).unwrap())
+        .collect();
+
+        let filter = ActivityFilter {
+            number_of_activities: None,
+            from_date: NaiveDate::from_ymd_opt(2024, 3, 1),
+            to_date: NaiveDate::from_ymd_opt(2024, 3, 31),
+            date: None,
+            project: Some("p1"),
+            tag: None,
+        };
+
+        let clipped = clip_activities(activities.iter().collect(), &filter);
+        let datetime = |s: &str| chrono::NaiveDateTime::parse_from_str(s, "%F %R").unwrap();
+
+        assert_eq!(clipped.len(), 3);
+        assert_eq!(clipped[0].description, "before");
+        assert_eq!(clipped[0].start, datetime("2024-03-01 00:00"));
+        assert_eq!(clipped[0].end, Some(datetime("2024-03-01 01:00")));
+        assert_eq!(clipped[1].start, activities[1].start);
+        assert_eq!(clipped[1].end, activities[1].end);
+        assert_eq!(clipped[2].description, "after");
+        assert_eq!(clipped[2].start, datetime("2024-03-31 22:00"));
+        assert_eq!(clipped[2].end, Some(datetime("2024-04-01 00:00")));
+    }
+
+    #[test]
+    fn clip_running_activity() {
+        let a = activity::Activity::from_str("2024-03-01 22:00 | p1 | running").unwrap();
+        let mut filter = ActivityFilter {
+            number_of_activities: None,
+            from_date: None,
+            to_date: None,
+            date: NaiveDate::from_ymd_opt(2024, 3, 1),
+            project: None,
+            tag: None,
+        };
+
+        let clipped = filter.clip(&a).unwrap();
+        assert_eq!(clipped.get_duration(), chrono::Duration::hours(2));
+
+        // without an end of the range the activity keeps running
+        filter.date = None;
+        filter.from_date = NaiveDate::from_ymd_opt(2024, 3, 1);
+        assert_eq!(filter.clip(&a).unwrap().end, None);
+    }
+
+    #[test]
+    fn get_overlapping_activities_test() {
+        let file_content = vec![
+            bartib_file::Line::new("2024-03-01 09:00 - 2024-03-01 10:00 | p1 | d1", 1),
+            bartib_file::Line::new("2024-03-01 10:00 - 2024-03-01 11:00 | p1 | d2", 2),
+            bartib_file::Line::new("2024-03-01 12:00 - 2024-03-01 13:00 | p1 | d3", 3),
+            bartib_file::Line::new("2024-03-02 08:00 | p1 | d4", 4),
+        ];
+
+        let new_activity = |from: &str, to: &str| {
+            activity::Activity::from_str(&format!("{from} - {to} | p2 | new")).unwrap()
+        };
+
+        let overlapping = get_overlapping_activities(
+            &file_content,
+            &new_activity("2024-03-01 09:30", "2024-03-01 10:30"),
+        );
+        assert_eq!(overlapping.len(), 2);
+
+        let overlapping = get_overlapping_activities(
+            &file_content,
+            &new_activity("2024-03-01 11:00", "2024-03-01 12:00"),
+        );
+        assert!(overlapping.is_empty());
+
+        let overlapping = get_overlapping_activities(
+            &file_content,
+            &new_activity("2024-03-02 09:00", "2024-03-02 10:00"),
+        );
+        assert_eq!(overlapping.len(), 1);
+        assert_eq!(overlapping[0].description, "d4");
+    }
+
+    #[test]
+    fn get_paused_activities_test() {
+        let mut file_content = vec![
+            bartib_file::Line::new(
+                "2024-03-01 09:00 - 2024-03-01 10:00 | p1 | d1 | (paused)",
+                1,
+            ),
+            bartib_file::Line::new(
+                "2024-03-01 11:00 - 2024-03-01 12:00 | p1 | d1 | (resumed) (paused)",
+                2,
+            ),
+        ];
+
+        let paused = get_paused_activities(&file_content);
+        assert_eq!(paused.len(), 1);
+        assert_eq!(
+            paused[0].start,
+            file_content[1].activity.as_ref().unwrap().start
+        );
+
+        // starting another activity ends the pause
+        file_content.push(bartib_file::Line::new("2024-03-01 12:00 | p2 | d2", 3));
+        assert!(get_paused_activities(&file_content).is_empty());
+    }
 }
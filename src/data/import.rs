@@ -0,0 +1,554 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::data::activity;
+use crate::data::activity::Activity;
+use crate::data::bartib_file::LineKind;
+use crate::data::timezone;
+
+// the formats of other time trackers bartib is able to import
+pub enum ImportFormat {
+    // the data files of timewarrior (`inc ...` lines) or the output of `timew export`
+    Timewarrior,
+    // the frames file of watson or the output of `watson log --json`
+    Watson,
+    // the detailed report of toggl track exported as csv
+    TogglCsv,
+    // another bartib file
+    Bartib,
+}
+
+static TIMEWARRIOR_DATETIME: &str = "%Y%m%dT%H%M%SZ";
+
+// parses the content of a file in the given format to activities
+pub fn parse_activities(content: &str, format: &ImportFormat) -> Result<Vec<Activity>> {
+    parse_activities_in(content, format, &Local)
+}
+
+// parses the content of a file in the given format to activities in the given time zone
+//
+// times recorded in UTC are converted together with their offsets, so times within the hour
+// that is repeated when the clocks fall back keep apart
+fn parse_activities_in<Tz: TimeZone>(
+    content: &str,
+    format: &ImportFormat,
+    tz: &Tz,
+) -> Result<Vec<Activity>> {
+    let to_zone = |activities: Vec<Activity>| {
+        activities
+            .into_iter()
+            .map(|activity| to_zone(tz, activity))
+            .collect()
+    };
+
+    match format {
+        ImportFormat::Timewarrior => parse_timewarrior(content).map(to_zone),
+        ImportFormat::Watson => parse_watson(content).map(to_zone),
+        ImportFormat::TogglCsv => parse_toggl_csv(content),
+        ImportFormat::Bartib => parse_bartib(content),
+    }
+}
+
+fn parse_bartib(content: &str) -> Result<Vec<Activity>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| LineKind::of(line) == LineKind::Activity)
+        .map(|(i, line)| {
+            Activity::from_str(line).with_context(|| format!("Could not parse line {}", i + 1))
+        })
+        .collect()
+}
+
+/*
+    timewarrior tracks intervals with a list of tags but without a project. The first tag is used
+    as project, the other tags become the tags of the activity. The annotation is used as
+    description. Without an annotation the remaining tags make up the description.
+*/
+fn parse_timewarrior(content: &str) -> Result<Vec<Activity>> {
+    if content.trim_start().starts_with('[') {
+        let intervals: Vec<Value> =
+            serde_json::from_str(content).context("Could not parse timewarrior export")?;
+
+        intervals
+            .iter()
+            .enumerate()
+            .map(|(i, interval)| {
+                parse_timewarrior_json_interval(interval)
+                    .with_context(|| format!("Could not parse interval {}", i + 1))
+            })
+            .collect()
+    } else {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                parse_timewarrior_line(line)
+                    .with_context(|| format!("Could not parse line {}", i + 1))
+            })
+            .collect()
+    }
+}
+
+fn parse_timewarrior_json_interval(interval: &Value) -> Result<Activity> {
+    let start = parse_timewarrior_datetime(get_str(interval, "start")?)?;
+    let end = match interval.get("end").and_then(Value::as_str) {
+        Some(end) => Some(parse_timewarrior_datetime(end)?),
+        None => None,
+    };
+    let tags = get_string_list(interval, "tags");
+    let annotation = interval.get("annotation").and_then(Value::as_str);
+
+    Ok(activity_from_timewarrior(start, end, tags, annotation))
+}
+
+// parses a line like `inc 20240301T080000Z - 20240301T090000Z # tag1 "tag 2" # "annotation"`
+fn parse_timewarrior_line(line: &str) -> Result<Activity> {
+    let line = line
+        .trim()
+        .strip_prefix("inc ")
+        .ok_or_else(|| anyhow!("timewarrior intervals must start with `inc`"))?;
+
+    let mut parts = line.splitn(3, " # ");
+    let time_part = parts.next().unwrap_or("").trim_end_matches('#');
+    let tags = parts.next().map(split_timewarrior_tags).unwrap_or_default();
+    let annotation = parts.next().map(|a| a.trim().trim_matches('"'));
+
+    let mut times = time_part.split(" - ");
+    let start = parse_timewarrior_datetime(times.next().unwrap_or(""))?;
+    let end = match times.next() {
+        Some(end) => Some(parse_timewarrior_datetime(end)?),
+        None => None,
+    };
+
+    Ok(activity_from_timewarrior(start, end, tags, annotation))
+}
+
+fn activity_from_timewarrior(
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    mut tags: Vec<String>,
+    annotation: Option<&str>,
+) -> Activity {
+    let project = if tags.is_empty() {
+        String::new()
+    } else {
+        tags.remove(0)
+    };
+    let description = match annotation {
+        Some(annotation) if !annotation.is_empty() => annotation.to_string(),
+        _ => tags.join(", "),
+    };
+
+    let mut activity = activity_in_utc(project, description, start, end);
+    activity.tags = to_bartib_tags(&tags);
+    activity
+}
+
+// splits the tags of a timewarrior interval. Tags containing whitespace are quoted
+fn split_timewarrior_tags(s: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tags.push(current.clone());
+                    current.clear();
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        tags.push(current);
+    }
+
+    tags
+}
+
+fn parse_timewarrior_datetime(s: &str) -> Result<DateTime<Utc>> {
+    let datetime = NaiveDateTime::parse_from_str(s.trim(), TIMEWARRIOR_DATETIME)
+        .with_context(|| format!("Could not parse \"{}\" as timestamp", s.trim()))?;
+    Ok(Utc.from_utc_datetime(&datetime))
+}
+
+/*
+    watson tracks frames with a project and a list of tags but without a description. The tags
+    become the tags of the activity and make up its description.
+*/
+fn parse_watson(content: &str) -> Result<Vec<Activity>> {
+    let frames: Vec<Value> =
+        serde_json::from_str(content).context("Could not parse watson frames")?;
+
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            parse_watson_frame(frame).with_context(|| format!("Could not parse frame {}", i + 1))
+        })
+        .collect()
+}
+
+fn parse_watson_frame(frame: &Value) -> Result<Activity> {
+    let (start, end, project, tags) = match frame {
+        // the frames file: [start, stop, project, id, tags, updated_at]
+        Value::Array(fields) => {
+            let start = parse_unix_timestamp(fields.first())?;
+            let end = parse_unix_timestamp(fields.get(1))?;
+            let project = fields
+                .get(2)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("frame has no project"))?;
+            let tags = fields.get(4).map(to_string_list).unwrap_or_default();
+            (start, end, project, tags)
+        }
+        // the output of `watson log --json`
+        Value::Object(_) => {
+            let start = parse_rfc3339(get_str(frame, "start")?)?;
+            let end = parse_rfc3339(get_str(frame, "stop")?)?;
+            let project = get_str(frame, "project")?;
+            let tags = get_string_list(frame, "tags");
+            (start, end, project, tags)
+        }
+        _ => bail!("frame is neither a list nor an object"),
+    };
+
+    let mut activity = activity_in_utc(project.to_string(), tags.join(", "), start, Some(end));
+    activity.tags = to_bartib_tags(&tags);
+    Ok(activity)
+}
+
+fn parse_unix_timestamp(value: Option<&Value>) -> Result<DateTime<Utc>> {
+    let timestamp = value
+        .and_then(Value::as_f64)
+        .ok_or_else(|| anyhow!("frame has no valid timestamp"))?;
+    let datetime = DateTime::from_timestamp(timestamp as i64, 0)
+        .ok_or_else(|| anyhow!("timestamp {timestamp} is out of range"))?;
+    Ok(datetime)
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>> {
+    let datetime = DateTime::parse_from_rfc3339(s)
+        .with_context(|| format!("Could not parse \"{s}\" as timestamp"))?;
+    Ok(datetime.with_timezone(&Utc))
+}
+
+/*
+    the detailed report of toggl track contains the columns "Project", "Description",
+    "Start date", "Start time", "End date", "End time" and "Tags" among others. The columns are
+    identified by their names in the header row. Times are local times.
+*/
+fn parse_toggl_csv(content: &str) -> Result<Vec<Activity>> {
+    let mut rows = parse_csv(content).into_iter();
+    let header = rows.next().context("The csv file is empty")?;
+
+    let column = |name: &str| -> Result<usize> {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .with_context(|| format!("Column \"{name}\" is missing"))
+    };
+
+    let project_column = column("Project")?;
+    let description_column = column("Description")?;
+    let start_date_column = column("Start date")?;
+    let start_time_column = column("Start time")?;
+    let end_date_column = column("End date")?;
+    let end_time_column = column("End time")?;
+    let tags_column = column("Tags").ok();
+
+    rows.enumerate()
+        .filter(|(_, row)| row.iter().any(|cell| !cell.trim().is_empty()))
+        .map(|(i, row)| {
+            let cell = |column: usize| row.get(column).map_or("", |c| c.trim());
+
+            let start = parse_toggl_datetime(cell(start_date_column), cell(start_time_column))
+                .with_context(|| format!("Could not parse row {}", i + 2))?;
+            let end = parse_toggl_datetime(cell(end_date_column), cell(end_time_column))
+                .with_context(|| format!("Could not parse row {}", i + 2))?;
+            let tags: Vec<String> = tags_column
+                .map(|c| {
+                    cell(c)
+                        .split(',')
+                        .map(|t| t.trim().to_string())
+                        .filter(|t| !t.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let mut activity = Activity::start(
+                cell(project_column).to_string(),
+                cell(description_column).to_string(),
+                Some(start),
+            );
+            activity.set_end(Some(end));
+            activity.tags = to_bartib_tags(&tags);
+            Ok(activity)
+        })
+        .collect()
+}
+
+fn parse_toggl_datetime(date: &str, time: &str) -> Result<NaiveDateTime> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("Could not parse \"{date}\" as date"))?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .with_context(|| format!("Could not parse \"{time}\" as time"))?;
+    Ok(date.and_time(time))
+}
+
+// splits csv content into rows of cells. Quoted cells may contain commas, quotes and line breaks
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut cell)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => cell.push(c),
+        }
+    }
+
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    rows
+}
+
+// tags in bartib must not contain whitespace
+fn to_bartib_tags(tags: &[String]) -> Vec<String> {
+    let tags: Vec<String> = tags.iter().map(|t| t.replace(' ', "-")).collect();
+    activity::parse_tags(&tags.join(" "))
+}
+
+// an activity with times in UTC. It is converted to the local time zone by `to_zone`
+fn activity_in_utc(
+    project: String,
+    description: String,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+) -> Activity {
+    let mut activity = Activity::start(project, description, Some(start.naive_utc()));
+    activity.start_offset = Some(Utc.fix());
+    activity.end = end.map(|end| end.naive_utc());
+    activity.end_offset = end.map(|_| Utc.fix());
+    activity
+}
+
+// converts the times of an activity to the time zone, together with the offsets they have there
+fn to_zone<Tz: TimeZone>(tz: &Tz, mut activity: Activity) -> Activity {
+    let (start, start_offset) = timezone::to_zone(tz, &activity.start, activity.start_offset);
+    activity.start = start;
+    activity.start_offset = Some(start_offset);
+
+    if let Some(end) = activity.end {
+        let (end, end_offset) = timezone::to_zone(tz, &end, activity.end_offset);
+        activity.end = Some(end);
+        activity.end_offset = Some(end_offset);
+    }
+
+    activity
+}
+
+fn get_str<'a>(value: &'a Value, key: &str) -> Result<&'a str> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .with_context(|| format!("\"{key}\" is missing"))
+}
+
+fn get_string_list(value: &Value, key: &str) -> Vec<String> {
+    value.get(key).map(to_string_list).unwrap_or_default()
+}
+
+fn to_string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+    use crate::data::timezone::tests::{datetime, Cet2024};
+
+    // the local time of a time in UTC, shifted by the fixed offset of the local time zone
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        let utc = NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap();
+        let offset = Local.offset_from_utc_datetime(&utc).fix();
+        utc + chrono::Duration::seconds(i64::from(offset.local_minus_utc()))
+    }
+
+    #[test]
+    fn import_timewarrior_data_file() {
+        let content =
+            "inc 20240301T080000Z - 20240301T093000Z # client \"code review\" # \"PR 42\"\n\
+                       inc 20240301T100000Z - 20240301T110000Z # internal meeting\n\
+                       inc 20240301T120000Z\n";
+
+        let activities = parse_activities(content, &ImportFormat::Timewarrior).unwrap();
+
+        assert_eq!(activities.len(), 3);
+        assert_eq!(activities[0].project, "client");
+        assert_eq!(activities[0].description, "PR 42");
+        assert_eq!(activities[0].tags, vec!["code-review".to_string()]);
+        assert_eq!(activities[0].start, utc(2024, 3, 1, 8, 0));
+        assert_eq!(activities[0].end, Some(utc(2024, 3, 1, 9, 30)));
+        assert_eq!(activities[1].project, "internal");
+        assert_eq!(activities[1].description, "meeting");
+        assert_eq!(activities[2].project, "");
+        assert_eq!(activities[2].end, None);
+    }
+
+    #[test]
+    fn import_timewarrior_export() {
+        let content = r#"[
+            {"id":2,"start":"20240301T080000Z","end":"20240301T093000Z","tags":["client","review"],"annotation":"PR 42"},
+            {"id":1,"start":"20240301T100000Z","tags":["internal"]}
+        ]"#;
+
+        let activities = parse_activities(content, &ImportFormat::Timewarrior).unwrap();
+
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[0].project, "client");
+        assert_eq!(activities[0].description, "PR 42");
+        assert_eq!(activities[0].end, Some(utc(2024, 3, 1, 9, 30)));
+        assert_eq!(activities[1].project, "internal");
+        assert_eq!(activities[1].end, None);
+    }
+
+    #[test]
+    fn import_watson_frames() {
+        let content = r#"[
+            [1709280000, 1709285400, "client", "a1b2", ["review", "backend"], 1709285400],
+            [1709290000, 1709293600, "internal", "c3d4", [], 1709293600]
+        ]"#;
+
+        let activities = parse_activities(content, &ImportFormat::Watson).unwrap();
+
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[0].project, "client");
+        assert_eq!(activities[0].description, "review, backend");
+        assert_eq!(
+            activities[0].tags,
+            vec!["review".to_string(), "backend".to_string()]
+        );
+        assert_eq!(activities[0].start, utc(2024, 3, 1, 8, 0));
+        assert_eq!(activities[0].end, Some(utc(2024, 3, 1, 9, 30)));
+        assert_eq!(activities[1].description, "");
+    }
+
+    #[test]
+    fn import_watson_log() {
+        let content = r#"[{"id":"a1b2","project":"client","start":"2024-03-01T09:00:00+01:00","stop":"2024-03-01T10:30:00+01:00","tags":["review"]}]"#;
+
+        let activities = parse_activities(content, &ImportFormat::Watson).unwrap();
+
+        assert_eq!(activities.len(), 1);
+        assert_eq!(activities[0].project, "client");
+        assert_eq!(activities[0].start, utc(2024, 3, 1, 8, 0));
+        assert_eq!(activities[0].end, Some(utc(2024, 3, 1, 9, 30)));
+    }
+
+    #[test]
+    fn import_toggl_csv() {
+        let content = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+                       Alice,alice@example.com,ACME,Website,,\"Fix header, footer\",Yes,2024-03-01,09:00:00,2024-03-01,10:30:00,01:30:00,\"design, urgent\"\n\
+                       Alice,alice@example.com,,Internal,,\"Say \"\"hi\"\"\",No,2024-03-01,23:00:00,2024-03-02,01:00:00,02:00:00,\n";
+
+        let activities = parse_activities(content, &ImportFormat::TogglCsv).unwrap();
+
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[0].project, "Website");
+        assert_eq!(activities[0].description, "Fix header, footer");
+        assert_eq!(
+            activities[0].tags,
+            vec!["design".to_string(), "urgent".to_string()]
+        );
+        assert_eq!(
+            activities[0].start,
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap()
+        );
+        assert_eq!(activities[1].description, "Say \"hi\"");
+        assert_eq!(
+            activities[1].end,
+            NaiveDate::from_ymd_opt(2024, 3, 2)
+                .unwrap()
+                .and_hms_opt(1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn import_across_fall_back() {
+        let content = "inc 20241027T003000Z - 20241027T013000Z # project\n";
+
+        let activities =
+            parse_activities_in(content, &ImportFormat::Timewarrior, &Cet2024).unwrap();
+
+        // both times are 02:30 local time, but an hour apart
+        let a = &activities[0];
+        assert_eq!(a.start, datetime(2024, 10, 27, 2, 30, 0));
+        assert_eq!(a.start_offset, FixedOffset::east_opt(7200));
+        assert_eq!(a.end, Some(datetime(2024, 10, 27, 2, 30, 0)));
+        assert_eq!(a.end_offset, FixedOffset::east_opt(3600));
+        assert_eq!(a.get_duration_in(&Cet2024), chrono::Duration::hours(1));
+
+        let a = Activity::from_str(&a.to_string()).unwrap();
+        assert_eq!(a.get_duration_in(&Cet2024), chrono::Duration::hours(1));
+    }
+
+    #[test]
+    fn import_toggl_csv_missing_column() {
+        let content = "Project,Description\nWebsite,Header\n";
+        assert!(parse_activities(content, &ImportFormat::TogglCsv).is_err());
+    }
+
+    #[test]
+    fn import_bartib() {
+        let content = "2024-03-01 09:00 - 2024-03-01 10:30 | client | review\n\n# meetings\n2024-03-01 11:00 | internal | meeting\n";
+
+        let activities = parse_activities(content, &ImportFormat::Bartib).unwrap();
+
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[1].project, "internal");
+        assert!(parse_activities("no activity", &ImportFormat::Bartib).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+use chrono::{Local, NaiveDateTime};
+use std::fs;
+use std::io;
+
+use crate::conf;
+
+// the journals of changes to a bartib file
+pub enum Journal {
+    // changes that may be undone
+    Undo,
+    // changes that have been undone and may be redone
+    Redo,
+}
+
+// a change of the lines of a bartib file
+//
+// at `position` the `removed` lines have been replaced by the `added` lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub position: usize,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+    pub time: NaiveDateTime,
+}
+
+impl Change {
+    // creates the change that turns `old` into `new`
+    //
+    // the change covers everything between the common beginning and the common end of both
+    // versions. Returns None if both versions are equal.
+    #[must_use]
+    pub fn between(old: &[String], new: &[String]) -> Option<Self> {
+        let prefix = old
+            .iter()
+            .zip(new.iter())
+            .take_while(|(o, n)| o == n)
+            .count();
+
+        if prefix == old.len() && prefix == new.len() {
+            return None;
+        }
+
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(o, n)| o == n)
+            .count();
+
+        Some(Self {
+            position: prefix,
+            removed: old[prefix..old.len() - suffix].to_vec(),
+            added: new[prefix..new.len() - suffix].to_vec(),
+            time: Local::now().naive_local(),
+        })
+    }
+
+    // applies the change to the lines of a file
+    //
+    // returns None if the lines do not contain the removed lines at the position of the change
+    #[must_use]
+    pub fn apply(&self, lines: &[String]) -> Option<Vec<String>> {
+        replace(lines, self.position, &self.removed, &self.added)
+    }
+
+    // reverts the change on the lines of a file
+    //
+    // returns None if the lines do not contain the added lines at the position of the change
+    #[must_use]
+    pub fn revert(&self, lines: &[String]) -> Option<Vec<String>> {
+        replace(lines, self.position, &self.added, &self.removed)
+    }
+}
+
+fn replace(
+    lines: &[String],
+    position: usize,
+    expected: &[String],
+    replacement: &[String],
+) -> Option<Vec<String>> {
+    let end = position.checked_add(expected.len())?;
+
+    if lines.get(position..end)? != expected {
+        return None;
+    }
+
+    let mut result: Vec<String> = lines[..position].to_vec();
+    result.extend_from_slice(replacement);
+    result.extend_from_slice(&lines[end..]);
+    Some(result)
+}
+
+// records the change from `old` to `new` in the undo journal
+//
+// as a new change invalidates all changes that have been undone before, the redo journal is
+// cleared
+pub fn record(file_name: &str, old: &[String], new: &[String]) -> io::Result<()> {
+    if let Some(change) = Change::between(old, new) {
+        push(file_name, &Journal::Undo, &change)?;
+        clear(file_name, &Journal::Redo)?;
+    }
+
+    Ok(())
+}
+
+// prints a warning if a journal could not be written
+//
+// the change of the bartib file has already been written at this point, so a broken journal
+// must not turn it into a failure
+pub fn warn_on_error(result: io::Result<()>) {
+    if let Err(e) = result {
+        eprintln!("Warning: Could not write to the journal: {e}");
+    }
+}
+
+// adds a change to a journal
+//
+// only the latest changes are kept in the journal
+pub fn push(file_name: &str, journal: &Journal, change: &Change) -> io::Result<()> {
+    let path = get_journal_path(file_name, journal);
+    let mut changes = read_changes(&path)?;
+    changes.push(change.clone());
+
+    let first = changes.len().saturating_sub(conf::MAX_JOURNAL_ENTRIES);
+    write_changes(&path, &changes[first..])
+}
+
+// removes the latest change from a journal
+pub fn pop(file_name: &str, journal: &Journal) -> io::Result<Option<Change>> {
+    let path = get_journal_path(file_name, journal);
+    let mut changes = read_changes(&path)?;
+    let change = changes.pop();
+
+    if change.is_some() {
+        write_changes(&path, &changes)?;
+    }
+
+    Ok(change)
+}
+
+// removes all changes from a journal
+pub fn clear(file_name: &str, journal: &Journal) -> io::Result<()> {
+    write_changes(&get_journal_path(file_name, journal), &[])
+}
+
+// the journals are kept next to the bartib file
+fn get_journal_path(file_name: &str, journal: &Journal) -> String {
+    match journal {
+        Journal::Undo => format!("{file_name}.undo"),
+        Journal::Redo => format!("{file_name}.redo"),
+    }
+}
+
+/*
+    each change is written as a header line followed by the removed and the added lines:
+
+    @@ POSITION NUMBER_OF_REMOVED_LINES NUMBER_OF_ADDED_LINES TIME
+    -removed line
+    +added line
+*/
+fn format_change(change: &Change) -> String {
+    let mut result = format!(
+        "@@ {} {} {} {}\n",
+        change.position,
+        change.removed.len(),
+        change.added.len(),
+        change.time.format(conf::FORMAT_DATETIME)
+    );
+
+    for line in &change.removed {
+        result.push_str(&format!("-{line}\n"));
+    }
+
+    for line in &change.added {
+        result.push_str(&format!("+{line}\n"));
+    }
+
+    result
+}
+
+fn parse_changes(content: &str) -> Option<Vec<Change>> {
+    let mut lines = content.lines();
+    let mut changes = Vec::new();
+
+    while let Some(header) = lines.next() {
+        let mut header_parts = header.strip_prefix("@@ ")?.splitn(4, ' ');
+        let position: usize = header_parts.next()?.parse().ok()?;
+        let number_of_removed_lines: usize = header_parts.next()?.parse().ok()?;
+        let number_of_added_lines: usize = header_parts.next()?.parse().ok()?;
+        let time =
+            NaiveDateTime::parse_from_str(header_parts.next()?, conf::FORMAT_DATETIME).ok()?;
+
+        let mut removed = Vec::with_capacity(number_of_removed_lines);
+        for _ in 0..number_of_removed_lines {
+            removed.push(lines.next()?.strip_prefix('-')?.to_string());
+        }
+
+        let mut added = Vec::with_capacity(number_of_added_lines);
+        for _ in 0..number_of_added_lines {
+            added.push(lines.next()?.strip_prefix('+')?.to_string());
+        }
+
+        changes.push(Change {
+            position,
+            removed,
+            added,
+            time,
+        });
+    }
+
+    Some(changes)
+}
+
+fn read_changes(path: &str) -> io::Result<Vec<Change>> {
+    match fs::read_to_string(path) {
+        Ok(content) => parse_changes(&content).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Could not read the journal {path}"),
+            )
+        }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn write_changes(path: &str, changes: &[Change]) -> io::Result<()> {
+    if changes.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+
+    let content: String = changes.iter().map(format_change).collect();
+    fs::write(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::bartib_file::tests::TestFile;
+
+    fn lines(l: &[&str]) -> Vec<String> {
+        l.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn between_equal() {
+        let old = lines(&["a", "b"]);
+        assert_eq!(Change::between(&old, &old), None);
+    }
+
+    #[test]
+    fn between_appended_line() {
+        let change = Change::between(&lines(&["a", "b"]), &lines(&["a", "b", "c"])).unwrap();
+
+        assert_eq!(change.position, 2);
+        assert!(change.removed.is_empty());
+        assert_eq!(change.added, lines(&["c"]));
+    }
+
+    #[test]
+    fn between_changed_line() {
+        let change =
+            Change::between(&lines(&["a", "b", "c"]), &lines(&["a", "x", "y", "c"])).unwrap();
+
+        assert_eq!(change.position, 1);
+        assert_eq!(change.removed, lines(&["b"]));
+        assert_eq!(change.added, lines(&["x", "y"]));
+    }
+
+    #[test]
+    fn apply_and_revert() {
+        let old = lines(&["a", "b", "c"]);
+        let new = lines(&["a", "c", "d"]);
+        let change = Change::between(&old, &new).unwrap();
+
+        assert_eq!(change.apply(&old).unwrap(), new);
+        assert_eq!(change.revert(&new).unwrap(), old);
+
+        // the file has been changed in the meantime
+        assert_eq!(change.revert(&lines(&["a"])), None);
+        assert_eq!(change.revert(&lines(&["a", "x", "d"])), None);
+    }
+
+    #[test]
+    fn format_and_parse() {
+        let changes = [
+            Change::between(&lines(&["a", "b"]), &lines(&["a", "@@ c", "-d"])).unwrap(),
+            Change::between(&lines(&[]), &lines(&["x"])).unwrap(),
+        ];
+
+        let content: String = changes.iter().map(format_change).collect();
+        let parsed = parse_changes(&content).unwrap();
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].position, changes[0].position);
+        assert_eq!(parsed[0].removed, changes[0].removed);
+        assert_eq!(parsed[0].added, changes[0].added);
+        assert_eq!(parsed[1].added, changes[1].added);
+
+        assert!(parse_changes("@@ 1 1 0 2024-03-01 10:00\n+wrong").is_none());
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let file = TestFile::new("journal");
+        let file_name = &file.name;
+
+        record(file_name, &lines(&["a"]), &lines(&["a", "b"])).unwrap();
+        record(file_name, &lines(&["a", "b"]), &lines(&["a", "c"])).unwrap();
+
+        let change = pop(file_name, &Journal::Undo).unwrap().unwrap();
+        assert_eq!(change.added, lines(&["c"]));
+        push(file_name, &Journal::Redo, &change).unwrap();
+
+        let change = pop(file_name, &Journal::Undo).unwrap().unwrap();
+        assert_eq!(change.added, lines(&["b"]));
+        assert_eq!(pop(file_name, &Journal::Undo).unwrap(), None);
+
+        // recording a new change discards the changes that might have been redone
+        record(file_name, &lines(&["a"]), &lines(&["d"])).unwrap();
+        assert_eq!(pop(file_name, &Journal::Redo).unwrap(), None);
+
+        clear(file_name, &Journal::Undo).unwrap();
+        assert_eq!(pop(file_name, &Journal::Undo).unwrap(), None);
+    }
+}
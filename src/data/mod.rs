@@ -1,6 +1,12 @@
 pub mod activity;
 pub mod bartib_file;
+pub mod billing;
 pub mod filter;
 pub mod getter;
+pub mod import;
+pub mod journal;
 pub mod processor;
 pub mod round_util;
+pub mod time_parser;
+pub mod timezone;
+pub mod working_time;
@@ -1,41 +1,163 @@
 use anyhow::Result;
-use chrono::Duration;
+use chrono::{Duration, Local};
 
 use crate::data::activity;
-use crate::data::round_util::round_datetime;
+use crate::data::round_util::{round_local_datetime, RoundStrategy, Rounding};
+use crate::data::timezone;
 
 pub type ProcessorList = Vec<Box<dyn ActivityProcessor>>;
 
+// maps an activity to one or more processed activities
 pub trait ActivityProcessor {
-    fn process(&self, activity: &activity::Activity) -> activity::Activity;
+    fn process(&self, activity: &activity::Activity) -> Vec<activity::Activity>;
 }
 
 pub struct StatusReportData<'a> {
     pub activity: Option<&'a activity::Activity>,
+    pub paused: Option<&'a activity::Activity>,
     pub project: Option<&'a str>,
     pub today: Duration,
     pub current_week: Duration,
     pub current_month: Duration,
+    // the total of the date range given on the command line, if any
+    pub range: Option<Duration>,
+    pub targets: Option<StatusTargets>,
+}
+
+impl StatusReportData<'_> {
+    // the state of the status: an activity is running, paused or there is no activity at all
+    #[must_use]
+    pub fn get_state(&self) -> &'static str {
+        if self.activity.is_some() {
+            "active"
+        } else if self.paused.is_some() {
+            "paused"
+        } else {
+            "idle"
+        }
+    }
+}
+
+// the target working times for the periods of a status report
+pub struct StatusTargets {
+    pub today: Duration,
+    pub current_week: Duration,
+    pub current_month: Duration,
+    // the overtime since the start of the working time model
+    pub balance: Option<Duration>,
 }
 pub trait StatusReportWriter {
     fn process(&self, data: &StatusReportData) -> Result<()>;
 }
 
+// shows the activities in the current time zone instead of the time zones they have been
+// recorded in
+pub struct CurrentZoneProcessor {}
+
+impl ActivityProcessor for CurrentZoneProcessor {
+    fn process(&self, activity: &activity::Activity) -> Vec<activity::Activity> {
+        let mut converted = activity.clone();
+
+        let (start, start_offset) =
+            timezone::to_zone(&Local, &activity.start, activity.start_offset);
+        converted.start = start;
+        converted.start_offset = Some(start_offset);
+
+        if let Some(end) = activity.end {
+            let (end, end_offset) = timezone::to_zone(&Local, &end, activity.end_offset);
+            converted.end = Some(end);
+            converted.end_offset = Some(end_offset);
+        }
+
+        vec![converted]
+    }
+}
+
+// rounds the start and the end of activities
 pub struct RoundProcessor {
     pub round: Duration,
+    pub strategy: RoundStrategy,
 }
 
 impl ActivityProcessor for RoundProcessor {
-    fn process(&self, activity: &activity::Activity) -> activity::Activity {
-        let start = round_datetime(&activity.start, &self.round);
-        let end = activity.end.map(|end| round_datetime(&end, &self.round));
+    fn process(&self, activity: &activity::Activity) -> Vec<activity::Activity> {
+        let mut rounded = activity.clone();
+
+        (rounded.start, rounded.start_offset) = round_local_datetime(
+            &Local,
+            &activity.start,
+            activity.start_offset,
+            &self.round,
+            self.strategy,
+        );
+
+        if let Some(end) = activity.end {
+            let (end, end_offset) = round_local_datetime(
+                &Local,
+                &end,
+                activity.end_offset,
+                &self.round,
+                self.strategy,
+            );
+            rounded.end = Some(end);
+            rounded.end_offset = end_offset;
+        }
+
+        vec![rounded]
+    }
+}
+
+// rounds the durations of stopped activities by moving their ends
+pub struct RoundDurationProcessor {
+    pub rounding: Rounding,
+}
+
+impl ActivityProcessor for RoundDurationProcessor {
+    fn process(&self, activity: &activity::Activity) -> Vec<activity::Activity> {
+        let mut rounded = activity.clone();
+
+        if activity.is_stopped() {
+            rounded.set_duration(self.rounding.apply(activity.get_duration()));
+        }
+
+        vec![rounded]
+    }
+}
+
+// splits activities that span several days into one activity per day
+//
+// a running activity is split up to now, its last part keeps running. Only the last part of
+// a paused activity stays paused, so it is the one which will be resumed, and only the first
+// part of a resumed activity is marked as resumed.
+pub struct SplitAtMidnightProcessor {}
+
+impl ActivityProcessor for SplitAtMidnightProcessor {
+    fn process(&self, activity: &activity::Activity) -> Vec<activity::Activity> {
+        let end = activity.get_end_or_now();
+        let mut parts = Vec::new();
+        let mut rest = activity.clone();
+
+        while let Some(midnight) = rest
+            .start
+            .date()
+            .succ_opt()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+        {
+            if end <= midnight {
+                break;
+            }
 
-        activity::Activity {
-            start,
-            end,
-            project: activity.project.clone(),
-            description: activity.description.clone(),
+            let mut part = rest.clone();
+            part.move_end(midnight);
+            part.paused = false;
+            parts.push(part);
+
+            rest.move_start(midnight);
+            rest.resumed = false;
         }
+
+        parts.push(rest);
+        parts
     }
 }
 
@@ -45,11 +167,290 @@ pub fn process_activities(
 ) -> Vec<activity::Activity> {
     activities
         .into_iter()
-        .cloned()
-        .map(|activity| {
-            processors
-                .iter()
-                .fold(activity, |activity, processor| processor.process(&activity))
+        .flat_map(|activity| process_activity(activity, &processors))
+        .collect()
+}
+
+// processes activities together with their indexes. All parts of an activity keep its index
+pub fn process_indexed_activities(
+    activities: Vec<(Option<usize>, &activity::Activity)>,
+    processors: ProcessorList,
+) -> Vec<(Option<usize>, activity::Activity)> {
+    activities
+        .into_iter()
+        .flat_map(|(index, activity)| {
+            process_activity(activity, &processors)
+                .into_iter()
+                .map(move |processed| (index, processed))
         })
         .collect()
 }
+
+// rounds the total durations of all activities with the same group key, e.g. the same project
+// and description
+//
+// the difference to the rounded total is added to the last activities of each group, so all
+// durations derived from the activities add up to the rounded totals. Running activities are
+// left as they are, as they have no total yet.
+#[must_use]
+pub fn round_totals<K: PartialEq>(
+    mut activities: Vec<activity::Activity>,
+    rounding: &Rounding,
+    group_key: impl Fn(&activity::Activity) -> K,
+) -> Vec<activity::Activity> {
+    let mut groups: Vec<(K, Vec<usize>)> = Vec::new();
+
+    for (i, activity) in activities.iter().enumerate() {
+        if !activity.is_stopped() {
+            continue;
+        }
+
+        let key = group_key(activity);
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, indexes)) => indexes.push(i),
+            None => groups.push((key, vec![i])),
+        }
+    }
+
+    for (_, indexes) in groups {
+        let total: Duration = indexes.iter().map(|i| activities[*i].get_duration()).sum();
+        let mut difference = rounding.apply(total) - total;
+
+        for i in indexes.into_iter().rev() {
+            let activity = &mut activities[i];
+            let duration = activity.get_duration();
+            // an activity is shortened at most to a duration of zero
+            let change = if difference < -duration {
+                -duration
+            } else {
+                difference
+            };
+
+            activity.set_duration(duration + change);
+            difference -= change;
+
+            if difference.is_zero() {
+                break;
+            }
+        }
+    }
+
+    activities
+}
+
+fn process_activity(
+    activity: &activity::Activity,
+    processors: &ProcessorList,
+) -> Vec<activity::Activity> {
+    processors
+        .iter()
+        .fold(vec![activity.clone()], |activities, processor| {
+            activities
+                .iter()
+                .flat_map(|activity| processor.process(activity))
+                .collect()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+    use super::*;
+
+    fn activity(start: (u32, u32), end: Option<(u32, u32)>) -> activity::Activity {
+        let datetime = |(day, hour): (u32, u32)| {
+            NaiveDate::from_ymd_opt(2024, 3, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+
+        let mut a = activity::Activity::start(
+            "project".to_string(),
+            "description".to_string(),
+            Some(datetime(start)),
+        );
+        a.end = end.map(datetime);
+        a
+    }
+
+    #[test]
+    fn split_at_midnight() {
+        let mut a = activity((1, 22), Some((3, 2)));
+        a.paused = true;
+        a.resumed = true;
+
+        let parts = SplitAtMidnightProcessor {}.process(&a);
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].start, a.start);
+        assert_eq!(parts[0].end, Some(activity((2, 0), None).start));
+        assert_eq!(parts[1].start, activity((2, 0), None).start);
+        assert_eq!(parts[1].end, Some(activity((3, 0), None).start));
+        assert_eq!(parts[2].start, activity((3, 0), None).start);
+        assert_eq!(parts[2].end, a.end);
+        assert!(!parts[0].paused && !parts[1].paused && parts[2].paused);
+        assert!(parts[0].resumed && !parts[1].resumed && !parts[2].resumed);
+        assert_eq!(
+            parts
+                .iter()
+                .map(activity::Activity::get_duration)
+                .sum::<Duration>(),
+            a.get_duration()
+        );
+    }
+
+    #[test]
+    fn split_at_midnight_in_recorded_time_zone() {
+        let offset = FixedOffset::west_opt(4 * 3600);
+        let mut a = activity((1, 22), Some((2, 2)));
+        a.start_offset = offset;
+        a.end_offset = offset;
+
+        let parts = SplitAtMidnightProcessor {}.process(&a);
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].end, Some(activity((2, 0), None).start));
+        assert_eq!(parts[0].end_offset, offset);
+        assert_eq!(parts[1].start_offset, offset);
+        assert_eq!(parts[0].get_duration(), Duration::hours(2));
+        assert_eq!(parts[1].get_duration(), Duration::hours(2));
+    }
+
+    #[test]
+    fn current_zone() {
+        let mut a = activity((1, 9), Some((1, 17)));
+        a.start_offset = FixedOffset::west_opt(4 * 3600);
+        a.end_offset = FixedOffset::east_opt(9 * 3600);
+
+        let converted = CurrentZoneProcessor {}.process(&a);
+
+        assert_eq!(converted.len(), 1);
+        assert_eq!(converted[0].get_duration(), a.get_duration());
+        assert_eq!(
+            Local
+                .from_local_datetime(&converted[0].start)
+                .earliest()
+                .map(|start| start.naive_utc()),
+            Some(a.start + Duration::hours(4))
+        );
+    }
+
+    #[test]
+    fn split_at_midnight_keeps_activities_within_a_day() {
+        let a = activity((1, 8), Some((1, 17)));
+        let parts = SplitAtMidnightProcessor {}.process(&a);
+        assert_eq!(parts.len(), 1);
+        assert_eq!((parts[0].start, parts[0].end), (a.start, a.end));
+
+        // an activity ending at midnight does not need to be split
+        let a = activity((1, 22), Some((2, 0)));
+        let parts = SplitAtMidnightProcessor {}.process(&a);
+        assert_eq!(parts.len(), 1);
+        assert_eq!((parts[0].start, parts[0].end), (a.start, a.end));
+    }
+
+    #[test]
+    fn split_running_activity() {
+        let a = activity((1, 22), None);
+        let parts = SplitAtMidnightProcessor {}.process(&a);
+
+        assert!(parts.len() > 1);
+        assert!(parts[..parts.len() - 1].iter().all(|p| p.end.is_some()));
+        assert_eq!(parts.last().unwrap().end, None);
+    }
+
+    #[test]
+    fn round_durations() {
+        let mut a = activity((1, 9), Some((1, 10)));
+        a.end = a.end.map(|end| end + Duration::minutes(1));
+        let processor = RoundDurationProcessor {
+            rounding: Rounding {
+                round: Some(Duration::minutes(15)),
+                strategy: RoundStrategy::Up,
+                minimum: None,
+            },
+        };
+
+        let rounded = processor.process(&a);
+
+        assert_eq!(rounded[0].start, a.start);
+        assert_eq!(rounded[0].get_duration(), Duration::minutes(75));
+
+        let running = activity((1, 9), None);
+        assert_eq!(processor.process(&running)[0].end, None);
+    }
+
+    #[test]
+    fn round_totals_of_groups() {
+        let mut a = activity((1, 9), Some((1, 10)));
+        a.end = a.end.map(|end| end - Duration::minutes(50));
+        let mut b = activity((1, 11), Some((1, 12)));
+        b.end = b.end.map(|end| end - Duration::minutes(55));
+        let mut c = activity((1, 13), Some((1, 14)));
+        c.description = "other".to_string();
+        c.end = c.end.map(|end| end - Duration::minutes(35));
+
+        let rounding = Rounding {
+            round: Some(Duration::minutes(15)),
+            strategy: RoundStrategy::Up,
+            minimum: None,
+        };
+
+        let by_description = |a: &activity::Activity| a.description.clone();
+
+        // a and b add up to 15 minutes, c has 25 minutes
+        let rounded = round_totals(
+            vec![a.clone(), b.clone(), c.clone()],
+            &rounding,
+            by_description,
+        );
+        assert_eq!(rounded[0].get_duration(), Duration::minutes(10));
+        assert_eq!(rounded[1].get_duration(), Duration::minutes(5));
+        assert_eq!(rounded[2].get_duration(), Duration::minutes(30));
+
+        // grouped by project all three add up to 40 minutes
+        let rounded = round_totals(vec![a.clone(), b, c], &rounding, |a| a.project.clone());
+        assert_eq!(rounded[0].get_duration(), Duration::minutes(10));
+        assert_eq!(rounded[1].get_duration(), Duration::minutes(5));
+        assert_eq!(rounded[2].get_duration(), Duration::minutes(30));
+
+        // running activities keep running
+        let running = activity((1, 9), None);
+        let rounded = round_totals(vec![a.clone(), running], &rounding, by_description);
+        assert_eq!(rounded[0].get_duration(), Duration::minutes(15));
+        assert_eq!(rounded[1].end, None);
+
+        let rounding = Rounding {
+            round: Some(Duration::hours(1)),
+            strategy: RoundStrategy::Down,
+            minimum: None,
+        };
+
+        // rounding down may shorten several activities
+        let rounded = round_totals(vec![a.clone(), a], &rounding, by_description);
+        assert_eq!(rounded[0].get_duration(), Duration::zero());
+        assert_eq!(rounded[1].get_duration(), Duration::zero());
+    }
+
+    #[test]
+    fn process_indexed_activities_keeps_indexes() {
+        let a = activity((1, 22), Some((2, 2)));
+        let b = activity((2, 8), Some((2, 9)));
+        let processors: ProcessorList = vec![
+            Box::new(RoundProcessor {
+                round: Duration::hours(1),
+                strategy: RoundStrategy::Nearest,
+            }),
+            Box::new(SplitAtMidnightProcessor {}),
+        ];
+
+        let processed = process_indexed_activities(vec![(Some(1), &a), (Some(2), &b)], processors);
+
+        assert_eq!(
+            processed.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![Some(1), Some(1), Some(2)]
+        );
+    }
+}
@@ -1,28 +1,115 @@
-// Utility functions for rounding datetimes.
+// Utility functions for rounding datetimes and durations.
 // Limitations:
 // - Cannot handle days properly.
-// - Does not consider DST changes, so rounding to hours may be off by an hour.
 // - Does not consider leap seconds.
+use chrono::{FixedOffset, TimeZone};
+
+use crate::data::timezone;
+
+// the direction in which values are rounded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundStrategy {
+    Nearest,
+    Up,
+    Down,
+}
+
+// what is rounded: the start and end of each activity, the duration of each activity or the
+// totals of a report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundMode {
+    Timestamps,
+    Durations,
+    Totals,
+}
+
+// how durations are rounded and the minimum duration they are extended to
+#[derive(Debug, Clone, Copy)]
+pub struct Rounding {
+    pub round: Option<chrono::Duration>,
+    pub strategy: RoundStrategy,
+    pub minimum: Option<chrono::Duration>,
+}
+
+impl Rounding {
+    #[must_use]
+    pub fn apply(&self, duration: chrono::Duration) -> chrono::Duration {
+        let rounded = self.round.map_or(duration, |round| {
+            round_duration(&duration, &round, self.strategy)
+        });
+
+        match self.minimum {
+            Some(minimum) if rounded < minimum => minimum,
+            _ => rounded,
+        }
+    }
+}
+
 pub fn round_datetime(
     datetime: &chrono::NaiveDateTime,
     round: &chrono::Duration,
+    strategy: RoundStrategy,
 ) -> chrono::NaiveDateTime {
     let timestamp = datetime.and_utc().timestamp();
-    let round_seconds = round.num_seconds();
-
-    let rounded_timestamp =
-        (timestamp as f64 / round_seconds as f64).round() as i64 * round_seconds;
+    let rounded_timestamp = round_seconds(timestamp, round.num_seconds(), strategy);
 
     chrono::DateTime::from_timestamp(rounded_timestamp, 0)
         .unwrap()
         .naive_utc()
 }
 
+// rounds a local timestamp by the clock of the time zone
+//
+// a result that falls within the hour skipped when the clocks spring forward is moved behind
+// it. A timestamp with the offset of another time zone is rounded by the clock of that zone.
+pub fn round_local_datetime<Tz: TimeZone>(
+    tz: &Tz,
+    datetime: &chrono::NaiveDateTime,
+    offset: Option<FixedOffset>,
+    round: &chrono::Duration,
+    strategy: RoundStrategy,
+) -> (chrono::NaiveDateTime, Option<FixedOffset>) {
+    let rounded = round_datetime(datetime, round, strategy);
+
+    match offset {
+        Some(_) => (
+            rounded,
+            timezone::move_offset(tz, datetime, offset, &rounded),
+        ),
+        None => timezone::from_utc(tz, &timezone::to_utc(tz, &rounded, None)),
+    }
+}
+
+pub fn round_duration(
+    duration: &chrono::Duration,
+    round: &chrono::Duration,
+    strategy: RoundStrategy,
+) -> chrono::Duration {
+    chrono::Duration::seconds(round_seconds(
+        duration.num_seconds(),
+        round.num_seconds(),
+        strategy,
+    ))
+}
+
+fn round_seconds(seconds: i64, round_seconds: i64, strategy: RoundStrategy) -> i64 {
+    let quotient = seconds as f64 / round_seconds as f64;
+
+    let rounded = match strategy {
+        RoundStrategy::Nearest => quotient.round(),
+        RoundStrategy::Up => quotient.ceil(),
+        RoundStrategy::Down => quotient.floor(),
+    };
+
+    rounded as i64 * round_seconds
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{Duration, NaiveDate};
 
     use super::*;
+    use crate::data::timezone::tests::{datetime, Cet2024};
 
     fn fake_date() -> NaiveDate {
         NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()
@@ -33,12 +120,20 @@ mod tests {
         let round = Duration::minutes(5);
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(13, 32, 30).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(13, 32, 30).unwrap(),
+                &round,
+                RoundStrategy::Nearest
+            ),
             fake_date().and_hms_opt(13, 35, 0).unwrap()
         );
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(13, 31, 1).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(13, 31, 1).unwrap(),
+                &round,
+                RoundStrategy::Nearest
+            ),
             fake_date().and_hms_opt(13, 30, 0).unwrap()
         );
     }
@@ -48,12 +143,20 @@ mod tests {
         let round = Duration::hours(8);
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(4, 0, 1).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(4, 0, 1).unwrap(),
+                &round,
+                RoundStrategy::Nearest
+            ),
             fake_date().and_hms_opt(8, 0, 0).unwrap()
         );
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(3, 59, 59).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(3, 59, 59).unwrap(),
+                &round,
+                RoundStrategy::Nearest
+            ),
             fake_date().and_hms_opt(0, 0, 0).unwrap()
         );
     }
@@ -63,8 +166,130 @@ mod tests {
         let round = Duration::minutes(10);
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(13, 5, 0).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(13, 5, 0).unwrap(),
+                &round,
+                RoundStrategy::Nearest
+            ),
             fake_date().and_hms_opt(13, 10, 0).unwrap()
         )
     }
+
+    #[test]
+    fn test_round_up_and_down() {
+        let round = Duration::minutes(15);
+
+        assert_eq!(
+            round_datetime(
+                &fake_date().and_hms_opt(13, 0, 1).unwrap(),
+                &round,
+                RoundStrategy::Up
+            ),
+            fake_date().and_hms_opt(13, 15, 0).unwrap()
+        );
+
+        assert_eq!(
+            round_datetime(
+                &fake_date().and_hms_opt(13, 14, 59).unwrap(),
+                &round,
+                RoundStrategy::Down
+            ),
+            fake_date().and_hms_opt(13, 0, 0).unwrap()
+        );
+
+        assert_eq!(
+            round_datetime(
+                &fake_date().and_hms_opt(13, 15, 0).unwrap(),
+                &round,
+                RoundStrategy::Up
+            ),
+            fake_date().and_hms_opt(13, 15, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_local_datetime_across_transitions() {
+        let round = Duration::minutes(30);
+        let one_hour = FixedOffset::east_opt(3600);
+
+        // 01:50 is rounded up to 02:00, which is skipped when the clocks spring forward
+        assert_eq!(
+            round_local_datetime(
+                &Cet2024,
+                &datetime(2024, 3, 31, 1, 50, 0),
+                None,
+                &round,
+                RoundStrategy::Up
+            ),
+            (datetime(2024, 3, 31, 3, 0, 0), None)
+        );
+
+        // the second 02:20 when the clocks fall back stays the second one
+        assert_eq!(
+            round_local_datetime(
+                &Cet2024,
+                &datetime(2024, 10, 27, 2, 20, 0),
+                one_hour,
+                &round,
+                RoundStrategy::Nearest
+            ),
+            (datetime(2024, 10, 27, 2, 30, 0), one_hour)
+        );
+
+        assert_eq!(
+            round_local_datetime(
+                &Cet2024,
+                &datetime(2024, 10, 27, 2, 50, 0),
+                one_hour,
+                &round,
+                RoundStrategy::Up
+            ),
+            (datetime(2024, 10, 27, 3, 0, 0), one_hour)
+        );
+    }
+
+    #[test]
+    fn test_round_duration() {
+        let round = Duration::minutes(15);
+
+        assert_eq!(
+            round_duration(&Duration::minutes(16), &round, RoundStrategy::Up),
+            Duration::minutes(30)
+        );
+        assert_eq!(
+            round_duration(&Duration::minutes(29), &round, RoundStrategy::Down),
+            Duration::minutes(15)
+        );
+        assert_eq!(
+            round_duration(&Duration::minutes(22), &round, RoundStrategy::Nearest),
+            Duration::minutes(15)
+        );
+    }
+
+    #[test]
+    fn test_rounding_with_minimum() {
+        let rounding = Rounding {
+            round: Some(Duration::minutes(15)),
+            strategy: RoundStrategy::Up,
+            minimum: Some(Duration::minutes(30)),
+        };
+
+        assert_eq!(rounding.apply(Duration::minutes(5)), Duration::minutes(30));
+        assert_eq!(rounding.apply(Duration::minutes(31)), Duration::minutes(45));
+
+        let minimum_only = Rounding {
+            round: None,
+            strategy: RoundStrategy::Nearest,
+            minimum: Some(Duration::minutes(30)),
+        };
+
+        assert_eq!(
+            minimum_only.apply(Duration::minutes(5)),
+            Duration::minutes(30)
+        );
+        assert_eq!(
+            minimum_only.apply(Duration::minutes(31)),
+            Duration::minutes(31)
+        );
+    }
 }
@@ -0,0 +1,268 @@
+// Parser for dates and times given on the command line.
+//
+// Besides absolute timestamps like `2024-03-01 09:00` it understands:
+// - times of the current day: `17:30`
+// - offsets from now: `-15m`, `-1h30m`, `10 minutes ago`, `an hour ago`
+// - days with an optional time: `yesterday 17:30`, `friday`, `last monday at 9:00`
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::conf;
+
+// parses a date and time relative to now
+#[must_use]
+pub fn parse_datetime(input: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let input = input.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+
+    match words.as_slice() {
+        [] => None,
+        ["now"] => Some(now),
+        // durations too long to be added to now are rejected, as they are no valid input
+        [offset] if offset.starts_with(['-', '+']) => now.checked_add_signed(parse_offset(offset)?),
+        [durations @ .., "ago"] => now.checked_sub_signed(parse_durations(durations)?),
+        _ => parse_day_and_time(&words, now),
+    }
+}
+
+// parses a date relative to today. Any date and time is accepted, its time is ignored
+#[must_use]
+pub fn parse_date(input: &str, now: NaiveDateTime) -> Option<NaiveDate> {
+    parse_datetime(input, now).map(|datetime| datetime.date())
+}
+
+// parses an offset like `-15m` or `+1h30m`
+fn parse_offset(offset: &str) -> Option<Duration> {
+    let (sign, duration) = offset.split_at(1);
+    let duration = parse_compact_duration(duration)?;
+
+    if sign == "-" {
+        Some(-duration)
+    } else {
+        Some(duration)
+    }
+}
+
+// parses durations like `1h30m`, `10 minutes` or `an hour 5 min`
+fn parse_durations(words: &[&str]) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut number: Option<i32> = None;
+
+    for word in words {
+        if let Some(n) = number.take() {
+            total = total.checked_add(&get_unit(word)?.checked_mul(n)?)?;
+        } else if let Ok(n) = word.parse() {
+            number = Some(n);
+        } else if *word == "a" || *word == "an" {
+            number = Some(1);
+        } else {
+            total = total.checked_add(&parse_compact_duration(word)?)?;
+        }
+    }
+
+    // a number without unit is not a valid duration
+    if words.is_empty() || number.is_some() {
+        None
+    } else {
+        Some(total)
+    }
+}
+
+// parses a duration without spaces like `1h30m`. Supports days, hours, minutes and seconds
+fn parse_compact_duration(duration: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut number_string = String::new();
+
+    for c in duration.chars() {
+        match c {
+            '0'..='9' => number_string.push(c),
+            'd' | 'h' | 'm' | 's' => {
+                let number: i64 = number_string.parse().ok()?;
+                number_string.clear();
+
+                let duration = match c {
+                    'd' => Duration::try_days(number),
+                    'h' => Duration::try_hours(number),
+                    'm' => Duration::try_minutes(number),
+                    _ => Duration::try_seconds(number),
+                }?;
+                total = total.checked_add(&duration)?;
+            }
+            _ => return None,
+        }
+    }
+
+    if duration.is_empty() || !number_string.is_empty() {
+        None
+    } else {
+        Some(total)
+    }
+}
+
+fn get_unit(word: &str) -> Option<Duration> {
+    match word.trim_end_matches('s') {
+        "sec" | "second" => Some(Duration::seconds(1)),
+        "min" | "minute" => Some(Duration::minutes(1)),
+        "hour" => Some(Duration::hours(1)),
+        "day" => Some(Duration::days(1)),
+        "week" => Some(Duration::weeks(1)),
+        _ => None,
+    }
+}
+
+// parses a day followed by an optional time. A time without a day refers to today, a day
+// without a time to its midnight
+fn parse_day_and_time(words: &[&str], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (day_words, time) = match words {
+        [day_words @ .., time] if parse_time(time).is_some() => {
+            let day_words = match day_words {
+                [day_words @ .., "at"] if !day_words.is_empty() => day_words,
+                _ => day_words,
+            };
+            (day_words, parse_time(time))
+        }
+        _ => (words, None),
+    };
+
+    let today = now.date();
+    let day = match day_words {
+        [] if time.is_some() => today,
+        ["today"] => today,
+        ["yesterday"] => today.pred_opt()?,
+        ["tomorrow"] => today.succ_opt()?,
+        ["last", weekday] => get_last_weekday(today, weekday.parse().ok()?, false),
+        [day] => match day.parse() {
+            Ok(weekday) => get_last_weekday(today, weekday, true),
+            Err(_) => NaiveDate::parse_from_str(day, conf::FORMAT_DATE).ok()?,
+        },
+        _ => return None,
+    };
+
+    Some(day.and_time(time.unwrap_or(NaiveTime::MIN)))
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .ok()
+}
+
+// the latest day of the week before today, or today itself if it may be included
+fn get_last_weekday(today: NaiveDate, weekday: Weekday, including_today: bool) -> NaiveDate {
+    let days_back =
+        (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+
+    if days_back == 0 && !including_today {
+        today - Duration::days(7)
+    } else {
+        today - Duration::days(i64::from(days_back))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wednesday
+    fn now() -> NaiveDateTime {
+        datetime("2024-03-06 12:00")
+    }
+
+    fn datetime(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%F %R").unwrap()
+    }
+
+    fn parse(s: &str) -> Option<NaiveDateTime> {
+        parse_datetime(s, now())
+    }
+
+    #[test]
+    fn absolute_datetimes() {
+        assert_eq!(
+            parse("2024-03-01 09:00"),
+            Some(datetime("2024-03-01 09:00"))
+        );
+        assert_eq!(
+            parse("2024-03-01 09:00:30"),
+            Some(datetime("2024-03-01 09:00") + Duration::seconds(30))
+        );
+        assert_eq!(parse("2024-03-01"), Some(datetime("2024-03-01 00:00")));
+        assert_eq!(parse("17:30"), Some(datetime("2024-03-06 17:30")));
+        assert_eq!(parse(" 9:05 "), Some(datetime("2024-03-06 09:05")));
+        assert_eq!(parse("now"), Some(now()));
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(parse("-15m"), Some(datetime("2024-03-06 11:45")));
+        assert_eq!(parse("-1h30m"), Some(datetime("2024-03-06 10:30")));
+        assert_eq!(parse("+2d"), Some(datetime("2024-03-08 12:00")));
+        assert_eq!(parse("10 minutes ago"), Some(datetime("2024-03-06 11:50")));
+        assert_eq!(
+            parse("1 Hour 5 mins ago"),
+            Some(datetime("2024-03-06 10:55"))
+        );
+        assert_eq!(parse("an hour ago"), Some(datetime("2024-03-06 11:00")));
+        assert_eq!(parse("15m ago"), Some(datetime("2024-03-06 11:45")));
+        assert_eq!(parse("2 weeks ago"), Some(datetime("2024-02-21 12:00")));
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(parse("yesterday 17:30"), Some(datetime("2024-03-05 17:30")));
+        assert_eq!(parse("today"), Some(datetime("2024-03-06 00:00")));
+        assert_eq!(
+            parse("tomorrow at 8:00"),
+            Some(datetime("2024-03-07 08:00"))
+        );
+        assert_eq!(parse("monday"), Some(datetime("2024-03-04 00:00")));
+        assert_eq!(parse("wednesday 9:00"), Some(datetime("2024-03-06 09:00")));
+        assert_eq!(parse("last monday"), Some(datetime("2024-03-04 00:00")));
+        assert_eq!(parse("last wed"), Some(datetime("2024-02-28 00:00")));
+        assert_eq!(
+            parse("last friday at 16:00"),
+            Some(datetime("2024-03-01 16:00"))
+        );
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            parse_date("last monday", now()),
+            NaiveDate::from_ymd_opt(2024, 3, 4)
+        );
+        assert_eq!(
+            parse_date("3 days ago", now()),
+            NaiveDate::from_ymd_opt(2024, 3, 3)
+        );
+        assert_eq!(
+            parse_date("2024-02-29", now()),
+            NaiveDate::from_ymd_opt(2024, 2, 29)
+        );
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("-15"), None);
+        assert_eq!(parse("-15x"), None);
+        assert_eq!(parse("ago"), None);
+        assert_eq!(parse("10 ago"), None);
+        assert_eq!(parse("10 parsecs ago"), None);
+        assert_eq!(parse("last"), None);
+        assert_eq!(parse("at 9:00"), None);
+        assert_eq!(parse("next monday"), None);
+        assert_eq!(parse("25:00"), None);
+        assert_eq!(parse("2024-02-30"), None);
+    }
+
+    #[test]
+    fn out_of_range_inputs() {
+        assert_eq!(parse("9999999999999d ago"), None);
+        assert_eq!(parse("-99999999999h"), None);
+        assert_eq!(parse("+99999999999h"), None);
+        assert_eq!(parse("99999999999 weeks ago"), None);
+        assert_eq!(parse("2000000000 weeks ago"), None);
+        assert_eq!(parse("99999999999999999999m ago"), None);
+        assert_eq!(parse("106751991167d 106751991167d ago"), None);
+    }
+}
@@ -0,0 +1,356 @@
+// Utility functions for converting the local timestamps of the activity log from and to UTC.
+//
+// Timestamps are written in local time. A timestamp may carry the offset to UTC it has been
+// recorded with, which keeps the time zone it has been recorded in. Timestamps without an
+// offset are interpreted in the local time zone:
+// - times within the hour that is repeated when the clocks fall back are read as the first
+//   occurrence of that time
+// - times that are skipped when the clocks spring forward are read as if the clocks had not
+//   been changed yet
+use chrono::{Duration, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone};
+
+// converts a local timestamp to UTC
+pub fn to_utc<Tz: TimeZone>(
+    tz: &Tz,
+    datetime: &NaiveDateTime,
+    offset: Option<FixedOffset>,
+) -> NaiveDateTime {
+    let offset = offset.unwrap_or_else(|| get_offset(tz, datetime));
+    *datetime - Duration::seconds(i64::from(offset.local_minus_utc()))
+}
+
+// converts a UTC timestamp to local time
+//
+// the offset is only returned if it is needed to tell the local time apart from the first
+// occurrence of the same time when the clocks fall back
+pub fn from_utc<Tz: TimeZone>(
+    tz: &Tz,
+    datetime: &NaiveDateTime,
+) -> (NaiveDateTime, Option<FixedOffset>) {
+    let local = tz.from_utc_datetime(datetime);
+    let naive = local.naive_local();
+    let offset = local.offset().fix();
+
+    if offset == get_offset(tz, &naive) {
+        (naive, None)
+    } else {
+        (naive, Some(offset))
+    }
+}
+
+// converts a timestamp to the time zone, together with the offset it has there
+pub fn to_zone<Tz: TimeZone>(
+    tz: &Tz,
+    datetime: &NaiveDateTime,
+    offset: Option<FixedOffset>,
+) -> (NaiveDateTime, FixedOffset) {
+    from_utc_with_offset(tz, &to_utc(tz, datetime, offset))
+}
+
+// adds a duration to a local timestamp
+//
+// the duration is added in UTC, so the result is correct even if the clocks have been changed
+// in between. A timestamp with the offset of another time zone stays in that time zone.
+pub fn add_duration<Tz: TimeZone>(
+    tz: &Tz,
+    datetime: &NaiveDateTime,
+    offset: Option<FixedOffset>,
+    duration: Duration,
+) -> (NaiveDateTime, Option<FixedOffset>) {
+    let utc = to_utc(tz, datetime, offset) + duration;
+
+    match offset {
+        Some(offset) if !is_valid_offset(tz, datetime, offset) => (
+            utc + Duration::seconds(i64::from(offset.local_minus_utc())),
+            Some(offset),
+        ),
+        Some(_) => {
+            let (local, offset) = from_utc_with_offset(tz, &utc);
+            (local, Some(offset))
+        }
+        None => from_utc(tz, &utc),
+    }
+}
+
+// the offset of a timestamp that is moved to another local time
+//
+// an offset of the time zone follows its changes of daylight saving time, the offset of another
+// time zone is kept
+pub fn move_offset<Tz: TimeZone>(
+    tz: &Tz,
+    from: &NaiveDateTime,
+    offset: Option<FixedOffset>,
+    to: &NaiveDateTime,
+) -> Option<FixedOffset> {
+    match offset {
+        Some(offset) if is_valid_offset(tz, to, offset) => Some(offset),
+        Some(offset) if is_valid_offset(tz, from, offset) => Some(get_offset(tz, to)),
+        offset => offset,
+    }
+}
+
+fn from_utc_with_offset<Tz: TimeZone>(
+    tz: &Tz,
+    datetime: &NaiveDateTime,
+) -> (NaiveDateTime, FixedOffset) {
+    let local = tz.from_utc_datetime(datetime);
+    (local.naive_local(), local.offset().fix())
+}
+
+// whether a timestamp with the given offset is a valid local time in the time zone
+fn is_valid_offset<Tz: TimeZone>(tz: &Tz, datetime: &NaiveDateTime, offset: FixedOffset) -> bool {
+    match tz.offset_from_local_datetime(datetime) {
+        LocalResult::Single(o) => o.fix() == offset,
+        LocalResult::Ambiguous(a, b) => a.fix() == offset || b.fix() == offset,
+        LocalResult::None => false,
+    }
+}
+
+// the offset by which a local timestamp without recorded offset is interpreted
+pub fn get_offset<Tz: TimeZone>(tz: &Tz, datetime: &NaiveDateTime) -> FixedOffset {
+    match tz.offset_from_local_datetime(datetime) {
+        LocalResult::Single(offset) => offset.fix(),
+        // the first occurrence has the larger offset. Time zones do not agree on the order in
+        // which they return the offsets
+        LocalResult::Ambiguous(a, b) => {
+            let (a, b) = (a.fix(), b.fix());
+            if a.local_minus_utc() > b.local_minus_utc() {
+                a
+            } else {
+                b
+            }
+        }
+        // a day before the skipped time the clocks have not been changed yet
+        LocalResult::None => tz
+            .offset_from_local_datetime(&(*datetime - Duration::days(1)))
+            .earliest()
+            .map_or_else(|| FixedOffset::east_opt(0).unwrap(), |offset| offset.fix()),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::*;
+
+    // a time zone with central european time in 2024: UTC+1, and UTC+2 from 2024-03-31 02:00
+    // (01:00 UTC) until 2024-10-27 03:00 (01:00 UTC)
+    #[derive(Clone, Copy, Debug)]
+    pub struct Cet2024;
+
+    fn summer_time() -> (NaiveDateTime, NaiveDateTime) {
+        (
+            datetime(2024, 3, 31, 1, 0, 0),
+            datetime(2024, 10, 27, 1, 0, 0),
+        )
+    }
+
+    fn offset(hours: i32) -> FixedOffset {
+        FixedOffset::east_opt(hours * 3600).unwrap()
+    }
+
+    pub fn datetime(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, s)
+            .unwrap()
+    }
+
+    impl TimeZone for Cet2024 {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            Cet2024
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let candidates: Vec<FixedOffset> = [offset(1), offset(2)]
+                .iter()
+                .copied()
+                .filter(|o| {
+                    let utc = *local - Duration::seconds(i64::from(o.local_minus_utc()));
+                    self.offset_from_utc_datetime(&utc) == *o
+                })
+                .collect();
+
+            match candidates.as_slice() {
+                [single] => LocalResult::Single(*single),
+                // ordered by offset like the local time zone of chrono does
+                [first, second] => LocalResult::Ambiguous(*first, *second),
+                _ => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let (start, end) = summer_time();
+            if *utc >= start && *utc < end {
+                offset(2)
+            } else {
+                offset(1)
+            }
+        }
+    }
+
+    #[test]
+    fn to_utc_without_offset() {
+        assert_eq!(
+            to_utc(&Cet2024, &datetime(2024, 1, 1, 12, 0, 0), None),
+            datetime(2024, 1, 1, 11, 0, 0)
+        );
+        assert_eq!(
+            to_utc(&Cet2024, &datetime(2024, 7, 1, 12, 0, 0), None),
+            datetime(2024, 7, 1, 10, 0, 0)
+        );
+        // the skipped hour is read as winter time
+        assert_eq!(
+            to_utc(&Cet2024, &datetime(2024, 3, 31, 2, 30, 0), None),
+            datetime(2024, 3, 31, 1, 30, 0)
+        );
+        // the repeated hour is read as its first occurrence
+        assert_eq!(
+            to_utc(&Cet2024, &datetime(2024, 10, 27, 2, 30, 0), None),
+            datetime(2024, 10, 27, 0, 30, 0)
+        );
+    }
+
+    #[test]
+    fn to_utc_with_offset() {
+        assert_eq!(
+            to_utc(&Cet2024, &datetime(2024, 10, 27, 2, 30, 0), Some(offset(1))),
+            datetime(2024, 10, 27, 1, 30, 0)
+        );
+        assert_eq!(
+            to_utc(&Utc, &datetime(2024, 1, 1, 12, 0, 0), Some(offset(-5))),
+            datetime(2024, 1, 1, 17, 0, 0)
+        );
+    }
+
+    #[test]
+    fn from_utc_records_offset_of_repeated_hour() {
+        assert_eq!(
+            from_utc(&Cet2024, &datetime(2024, 10, 27, 0, 30, 0)),
+            (datetime(2024, 10, 27, 2, 30, 0), None)
+        );
+        assert_eq!(
+            from_utc(&Cet2024, &datetime(2024, 10, 27, 1, 30, 0)),
+            (datetime(2024, 10, 27, 2, 30, 0), Some(offset(1)))
+        );
+        assert_eq!(
+            from_utc(&Cet2024, &datetime(2024, 3, 31, 1, 30, 0)),
+            (datetime(2024, 3, 31, 3, 30, 0), None)
+        );
+    }
+
+    #[test]
+    fn to_zone_converts_recorded_offset() {
+        assert_eq!(
+            to_zone(&Cet2024, &datetime(2024, 7, 1, 9, 0, 0), Some(offset(-4))),
+            (datetime(2024, 7, 1, 15, 0, 0), offset(2))
+        );
+        assert_eq!(
+            to_zone(&Cet2024, &datetime(2024, 1, 1, 9, 0, 0), None),
+            (datetime(2024, 1, 1, 9, 0, 0), offset(1))
+        );
+    }
+
+    #[test]
+    fn add_duration_keeps_recorded_offset() {
+        assert_eq!(
+            add_duration(
+                &Cet2024,
+                &datetime(2024, 3, 31, 1, 30, 0),
+                Some(offset(-4)),
+                Duration::hours(1)
+            ),
+            (datetime(2024, 3, 31, 2, 30, 0), Some(offset(-4)))
+        );
+    }
+
+    #[test]
+    fn add_duration_with_local_offset() {
+        assert_eq!(
+            add_duration(
+                &Cet2024,
+                &datetime(2024, 3, 31, 1, 30, 0),
+                Some(offset(1)),
+                Duration::hours(1)
+            ),
+            (datetime(2024, 3, 31, 3, 30, 0), Some(offset(2)))
+        );
+    }
+
+    #[test]
+    fn move_offset_follows_local_time_zone() {
+        // summer time ends between both times
+        assert_eq!(
+            move_offset(
+                &Cet2024,
+                &datetime(2024, 10, 26, 9, 0, 0),
+                Some(offset(2)),
+                &datetime(2024, 10, 28, 0, 0, 0)
+            ),
+            Some(offset(1))
+        );
+        // the second occurrence of the repeated hour stays the second one
+        assert_eq!(
+            move_offset(
+                &Cet2024,
+                &datetime(2024, 10, 27, 2, 20, 0),
+                Some(offset(1)),
+                &datetime(2024, 10, 27, 2, 30, 0)
+            ),
+            Some(offset(1))
+        );
+        assert_eq!(
+            move_offset(
+                &Cet2024,
+                &datetime(2024, 10, 26, 9, 0, 0),
+                Some(offset(-4)),
+                &datetime(2024, 10, 28, 0, 0, 0)
+            ),
+            Some(offset(-4))
+        );
+        assert_eq!(
+            move_offset(
+                &Cet2024,
+                &datetime(2024, 10, 26, 9, 0, 0),
+                None,
+                &datetime(2024, 10, 28, 0, 0, 0)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn add_duration_across_transitions() {
+        // spring forward: one hour after 01:30 is 03:30
+        assert_eq!(
+            add_duration(
+                &Cet2024,
+                &datetime(2024, 3, 31, 1, 30, 0),
+                None,
+                Duration::hours(1)
+            ),
+            (datetime(2024, 3, 31, 3, 30, 0), None)
+        );
+        // fall back: one hour after 02:30 is 02:30 again
+        assert_eq!(
+            add_duration(
+                &Cet2024,
+                &datetime(2024, 10, 27, 2, 30, 0),
+                None,
+                Duration::hours(1)
+            ),
+            (datetime(2024, 10, 27, 2, 30, 0), Some(offset(1)))
+        );
+    }
+}
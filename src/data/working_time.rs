@@ -0,0 +1,168 @@
+use chrono::{Duration, NaiveDate, Weekday};
+
+use crate::conf::WorkingTime;
+use crate::data::activity::Activity;
+use crate::data::filter;
+
+// the target and the actual working time of a period
+#[derive(Debug, PartialEq)]
+pub struct Period {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub target: Duration,
+    pub actual: Duration,
+}
+
+impl Period {
+    // calculates the target and the actual working time between two dates (both inclusive)
+    #[must_use]
+    pub fn new(
+        working_time: &WorkingTime,
+        activities: &[&Activity],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            target: get_target(working_time, from, to),
+            actual: get_actual(activities, from, to),
+        }
+    }
+
+    // the overtime of the period. Negative if less time has been spent than targeted
+    #[must_use]
+    pub fn get_balance(&self) -> Duration {
+        self.actual - self.target
+    }
+}
+
+// the target working time of all days between two dates (both inclusive)
+#[must_use]
+pub fn get_target(working_time: &WorkingTime, from: NaiveDate, to: NaiveDate) -> Duration {
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| working_time.get_hours(date))
+        .fold(Duration::zero(), |sum, hours| sum + hours)
+}
+
+// the time spent on activities that started between two dates (both inclusive)
+#[must_use]
+pub fn get_actual(activities: &[&Activity], from: NaiveDate, to: NaiveDate) -> Duration {
+    activities
+        .iter()
+        .filter(|activity| activity.start.date() >= from && activity.start.date() <= to)
+        .map(|activity| activity.get_duration())
+        .fold(Duration::zero(), |sum, duration| sum + duration)
+}
+
+// splits the time between two dates into weeks and calculates the working time of each week
+//
+// the first and the last week may be shorter than seven days
+#[must_use]
+pub fn get_weekly_periods(
+    working_time: &WorkingTime,
+    activities: &[&Activity],
+    from: NaiveDate,
+    to: NaiveDate,
+    week_start: Weekday,
+) -> Vec<Period> {
+    let mut periods = Vec::new();
+    let mut period_start = from;
+
+    while period_start <= to {
+        let week_end = filter::first_day_of_week(period_start, week_start) + Duration::days(6);
+        let period_end = week_end.min(to);
+
+        periods.push(Period::new(
+            working_time,
+            activities,
+            period_start,
+            period_end,
+        ));
+        period_start = period_end + Duration::days(1);
+    }
+
+    periods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    // eight hours from monday to friday. 2024-03-08 is a holiday
+    fn working_time() -> WorkingTime {
+        let mut hours_per_weekday = [Duration::hours(8); 7];
+        hours_per_weekday[5] = Duration::zero();
+        hours_per_weekday[6] = Duration::zero();
+
+        WorkingTime {
+            hours_per_weekday,
+            start: None,
+            holidays: vec![date(8)],
+        }
+    }
+
+    fn activity(day: u32, hours: i64) -> Activity {
+        let start = date(day).and_hms_opt(9, 0, 0).unwrap();
+        let mut a = Activity::start("p".to_string(), "d".to_string(), Some(start));
+        a.end = Some(start + Duration::hours(hours));
+        a
+    }
+
+    #[test]
+    fn get_target_test() {
+        let wt = working_time();
+
+        // 2024-03-04 is a monday
+        assert_eq!(get_target(&wt, date(4), date(4)), Duration::hours(8));
+        assert_eq!(get_target(&wt, date(4), date(10)), Duration::hours(32));
+        assert_eq!(get_target(&wt, date(9), date(10)), Duration::zero());
+        assert_eq!(get_target(&wt, date(10), date(9)), Duration::zero());
+    }
+
+    #[test]
+    fn get_actual_test() {
+        let a1 = activity(4, 9);
+        let a2 = activity(5, 7);
+        let a3 = activity(11, 8);
+        let activities = vec![&a1, &a2, &a3];
+
+        assert_eq!(
+            get_actual(&activities, date(4), date(10)),
+            Duration::hours(16)
+        );
+        assert_eq!(
+            get_actual(&activities, date(5), date(5)),
+            Duration::hours(7)
+        );
+    }
+
+    #[test]
+    fn get_weekly_periods_test() {
+        let a1 = activity(6, 9);
+        let a2 = activity(11, 8);
+        let activities = vec![&a1, &a2];
+
+        let periods = get_weekly_periods(
+            &working_time(),
+            &activities,
+            date(6),
+            date(12),
+            Weekday::Mon,
+        );
+
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].from, date(6));
+        assert_eq!(periods[0].to, date(10));
+        assert_eq!(periods[0].target, Duration::hours(16));
+        assert_eq!(periods[0].get_balance(), Duration::hours(-7));
+        assert_eq!(periods[1].from, date(11));
+        assert_eq!(periods[1].to, date(12));
+        assert_eq!(periods[1].get_balance(), Duration::hours(-8));
+    }
+}
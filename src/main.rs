@@ -1,12 +1,26 @@
 use std::borrow::Borrow;
 
 use anyhow::{bail, Context, Result};
-use bartib::view::status::StatusReport;
-use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime};
+use bartib::view::export::ExportFormat;
+use bartib::view::format_util;
+use bartib::view::report::{Billing, ReportGrouping};
+use bartib::view::status::{
+    I3barStatus, JsonStatus, LineStatus, StatusReport, TemplateStatus, WaybarStatus,
+};
+use bartib::view::template::{FieldKind, Template, ACTIVITY_FIELDS, STATUS_FIELDS};
+use chrono::{Local, NaiveDate, NaiveDateTime};
 use clap::{crate_version, App, AppSettings, Arg, ArgMatches, SubCommand};
 
+use bartib::conf;
+use bartib::controller::manipulation::ActivityEnd;
+use bartib::data::activity;
+use bartib::data::bartib_file;
+use bartib::data::filter;
 use bartib::data::getter::ActivityFilter;
+use bartib::data::import::ImportFormat;
 use bartib::data::processor;
+use bartib::data::round_util::{RoundMode, RoundStrategy, Rounding};
+use bartib::data::time_parser;
 
 #[cfg(windows)]
 use nu_ansi_term::enable_ansi_support;
@@ -21,26 +35,32 @@ fn main() -> Result<()> {
         .short("t")
         .long("time")
         .value_name("TIME")
-        .help("the time for changing the activity status (HH:MM)")
+        .help("the time for changing the activity status, e.g. 17:30, -15m, \"10 minutes ago\" or \"yesterday 17:30\"")
+        .allow_hyphen_values(true)
         .takes_value(true);
 
     let arg_from_date = Arg::with_name("from_date")
         .long("from")
         .value_name("FROM_DATE")
-        .help("begin of date range (inclusive)")
+        .help("begin of date range (inclusive), e.g. 2024-03-01, yesterday or \"last monday\"")
+        .allow_hyphen_values(true)
         .takes_value(true);
 
     let arg_to_date = Arg::with_name("to_date")
         .long("to")
         .value_name("TO_DATE")
-        .help("end of date range (inclusive)")
+        .help("end of date range (inclusive), e.g. 2024-03-01, yesterday or \"last monday\"")
+        .allow_hyphen_values(true)
         .takes_value(true);
 
     let arg_date = Arg::with_name("date")
         .short("d")
         .long("date")
         .value_name("DATE")
-        .help("show activities of a certain date only")
+        .help(
+            "show activities of a certain date only, e.g. 2024-03-01, yesterday or \"last monday\"",
+        )
+        .allow_hyphen_values(true)
         .required(false)
         .conflicts_with_all(&["from_date", "to_date"])
         .takes_value(true);
@@ -80,12 +100,96 @@ fn main() -> Result<()> {
         ])
         .takes_value(false);
 
+    let arg_current_month = Arg::with_name("current_month")
+        .long("current_month")
+        .help("show activities of the current month")
+        .required(false)
+        .conflicts_with_all(&get_other_range_arguments("current_month"))
+        .takes_value(false);
+
+    let arg_last_month = Arg::with_name("last_month")
+        .long("last_month")
+        .help("show activities of the last month")
+        .required(false)
+        .conflicts_with_all(&get_other_range_arguments("last_month"))
+        .takes_value(false);
+
+    let arg_current_year = Arg::with_name("current_year")
+        .long("current_year")
+        .help("show activities of the current year")
+        .required(false)
+        .conflicts_with_all(&get_other_range_arguments("current_year"))
+        .takes_value(false);
+
+    let arg_quarter = Arg::with_name("quarter")
+        .long("quarter")
+        .value_name("QUARTER")
+        .help("show activities of a quarter of the current year (1 to 4) or of another year (e.g. 2024-Q1)")
+        .required(false)
+        .conflicts_with_all(&get_other_range_arguments("quarter"))
+        .takes_value(true);
+
+    let arg_last_days = Arg::with_name("last_days")
+        .long("last_days")
+        .value_name("DAYS")
+        .help("show activities of the given number of days up to and including today")
+        .required(false)
+        .conflicts_with_all(&get_other_range_arguments("last_days"))
+        .takes_value(true);
+
+    let arg_month = Arg::with_name("month")
+        .long("month")
+        .value_name("MONTH")
+        .help("show activities of a month (YYYY-MM)")
+        .required(false)
+        .conflicts_with_all(&get_other_range_arguments("month"))
+        .takes_value(true);
+
+    let arg_week = Arg::with_name("week")
+        .long("week")
+        .value_name("WEEK")
+        .help("show activities of a week of the current year (e.g. 12) or of another year (e.g. 2024-W12)")
+        .required(false)
+        .conflicts_with_all(&get_other_range_arguments("week"))
+        .takes_value(true);
+
     let arg_group = Arg::with_name("round")
         .long("round")
         .help("rounds the start and end time to the nearest duration. Durations can be in minutes or hours. E.g. 15m or 4h")
         .required(false)
         .takes_value(true);
 
+    let arg_round_strategy = Arg::with_name("round_strategy")
+        .long("round_strategy")
+        .value_name("STRATEGY")
+        .help("the direction in which --round rounds")
+        .possible_values(&conf::ROUND_STRATEGIES)
+        .required(false)
+        .takes_value(true);
+
+    let arg_round_mode = Arg::with_name("round_mode")
+        .long("round_mode")
+        .value_name("MODE")
+        .help("what --round and --min_duration apply to: the start and end of each activity, the duration of each activity or the total of each activity in a report")
+        .possible_values(&conf::ROUND_MODES)
+        .required(false)
+        .takes_value(true);
+
+    let arg_min_duration = Arg::with_name("min_duration")
+        .long("min_duration")
+        .value_name("DURATION")
+        .help("extends shorter durations to this minimum duration. E.g. 15m")
+        .required(false)
+        .takes_value(true);
+
+    let arg_zone = Arg::with_name("zone")
+        .long("zone")
+        .value_name("ZONE")
+        .help("shows the times of activities in the time zone they have been recorded in or in the current time zone")
+        .possible_values(&conf::ZONES)
+        .required(false)
+        .takes_value(true);
+
     let arg_description = Arg::with_name("description")
         .short("d")
         .long("description")
@@ -100,6 +204,40 @@ fn main() -> Result<()> {
         .help("the project to which the new activity belongs")
         .takes_value(true);
 
+    let arg_tag = Arg::with_name("tag")
+        .long("tag")
+        .value_name("TAG")
+        .help("a tag for the activity (e.g. meeting or +meeting). May be given multiple times")
+        .multiple(true)
+        .number_of_values(1)
+        .takes_value(true);
+
+    let arg_note = Arg::with_name("note")
+        .long("note")
+        .value_name("NOTE")
+        .help("longer notes on the activity, e.g. links or outcomes")
+        .takes_value(true);
+
+    let arg_index = Arg::with_name("index")
+        .value_name("INDEX")
+        .help("the index of the activity (see subcommand `list`)")
+        .required(true)
+        .takes_value(true);
+
+    let arg_tag_filter = Arg::with_name("tag")
+        .long("tag")
+        .value_name("TAG")
+        .help("only activities with this tag. Supports '?' and '*' as wildcards")
+        .takes_value(true)
+        .required(false);
+
+    let arg_template = Arg::with_name("template")
+        .long("template")
+        .value_name("TEMPLATE")
+        .help("prints the output with a template like '{project}: {description} ({duration})'. See the manual for the fields of each subcommand")
+        .takes_value(true)
+        .required(false);
+
     let matches = App::new("bartib")
         .version(crate_version!())
         .author("Nikolas Schmidt-Voigt <nikolas.schmidt-voigt@posteo.de>")
@@ -116,13 +254,56 @@ To get started, view the `start` help with `bartib start --help`")
                 .env("BARTIB_FILE")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("no_color")
+                .long("no-color")
+                .help("disables colors and other text styles in the output")
+                .takes_value(false),
+        )
         .subcommand(
             SubCommand::with_name("start")
                 .about("starts a new activity")
                 .arg(arg_project.clone().required(true))
                 .arg(arg_description.clone().required(true))
+                .arg(&arg_tag)
+                .arg(&arg_note)
                 .arg(&arg_time),
         )
+        .subcommand(
+            SubCommand::with_name("add")
+                .about("adds an already completed activity")
+                .arg(arg_project.clone().required(true))
+                .arg(arg_description.clone().required(true))
+                .arg(&arg_tag)
+                .arg(
+                    Arg::with_name("start")
+                        .short("s")
+                        .long("start")
+                        .value_name("START")
+                        .help("the start of the activity (YYYY-MM-DD HH:MM, or HH:MM for today, or relative like -2h)")
+                        .allow_hyphen_values(true)
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("end")
+                        .short("e")
+                        .long("end")
+                        .value_name("END")
+                        .help("the end of the activity (YYYY-MM-DD HH:MM, or HH:MM for today, or relative like -2h)")
+                        .allow_hyphen_values(true)
+                        .required_unless("duration")
+                        .conflicts_with("duration")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("duration")
+                        .long("duration")
+                        .value_name("DURATION")
+                        .help("the duration of the activity instead of its end. E.g. 45m, 2h or 1h30m")
+                        .takes_value(true),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("continue")
                 .about("continues a previous activity")
@@ -136,6 +317,7 @@ To get started, view the `start` help with `bartib start --help`")
                         .takes_value(true)
                         .default_value("0"),
                 )
+                .arg(&arg_tag)
                 .arg(&arg_time),
         )
         .subcommand(
@@ -143,6 +325,7 @@ To get started, view the `start` help with `bartib start --help`")
                 .about("changes the current activity")
                 .arg(&arg_description)
                 .arg(&arg_project)
+                .arg(&arg_tag)
                 .arg(&arg_time),
         )
         .subcommand(
@@ -150,11 +333,82 @@ To get started, view the `start` help with `bartib start --help`")
                 .about("stops all currently running activities")
                 .arg(&arg_time),
         )
+        .subcommand(
+            SubCommand::with_name("pause")
+                .about("pauses all currently running activities")
+                .arg(&arg_time),
+        )
+        .subcommand(
+            SubCommand::with_name("resume")
+                .about("resumes the paused activities")
+                .arg(&arg_time),
+        )
         .subcommand(
             SubCommand::with_name("cancel").about("cancels all currently running activities"),
         )
         .subcommand(
-            SubCommand::with_name("current").about("lists all currently running activities"),
+            SubCommand::with_name("amend")
+                .about("changes a past activity")
+                .arg(&arg_index)
+                .arg(&arg_project)
+                .arg(&arg_description)
+                .arg(&arg_tag)
+                .arg(
+                    Arg::with_name("start")
+                        .short("s")
+                        .long("start")
+                        .value_name("START")
+                        .help("the new start of the activity (YYYY-MM-DD HH:MM, or HH:MM for today, or relative like -2h)")
+                        .allow_hyphen_values(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("end")
+                        .short("e")
+                        .long("end")
+                        .value_name("END")
+                        .help("the new end of the activity (YYYY-MM-DD HH:MM, or HH:MM for today, or relative like -2h)")
+                        .allow_hyphen_values(true)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("delete")
+                .about("deletes a past activity")
+                .arg(&arg_index),
+        )
+        .subcommand(
+            SubCommand::with_name("note")
+                .about("sets the notes of the running activity or of a past activity")
+                .arg(
+                    Arg::with_name("notes")
+                        .value_name("NOTES")
+                        .help("the notes. Empty notes remove the notes of the activity")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(arg_index.clone().short("i").long("index").required(false)),
+        )
+        .subcommand(
+            SubCommand::with_name("undo").about("reverts the latest change to the activity log"),
+        )
+        .subcommand(
+            SubCommand::with_name("redo").about("applies the latest reverted change again"),
+        )
+        .subcommand(
+            SubCommand::with_name("restore")
+                .about("lists the backups of the activity log or restores one of them")
+                .arg(
+                    Arg::with_name("number")
+                        .value_name("NUMBER")
+                        .help("the number of the backup to restore")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("current")
+                .about("lists all currently running activities")
+                .arg(&arg_template),
         )
         .subcommand(
             SubCommand::with_name("list")
@@ -166,7 +420,18 @@ To get started, view the `start` help with `bartib start --help`")
                 .arg(&arg_yesterday)
                 .arg(&arg_current_week)
                 .arg(&arg_last_week)
+                .arg(&arg_current_month)
+                .arg(&arg_last_month)
+                .arg(&arg_current_year)
+                .arg(&arg_quarter)
+                .arg(&arg_last_days)
+                .arg(&arg_month)
+                .arg(&arg_week)
                 .arg(&arg_group)
+                .arg(&arg_round_strategy)
+                .arg(&arg_round_mode)
+                .arg(&arg_min_duration)
+                .arg(&arg_zone)
                 .arg(
                     Arg::with_name("project")
                         .short("p")
@@ -176,6 +441,14 @@ To get started, view the `start` help with `bartib start --help`")
                         .takes_value(true)
                         .required(false),
                 )
+                .arg(&arg_tag_filter)
+                .arg(&arg_template)
+                .arg(
+                    Arg::with_name("verbose")
+                        .short("v")
+                        .long("verbose")
+                        .help("shows the notes of the activities"),
+                )
                 .arg(
                     Arg::with_name("no_grouping")
                         .long("no_grouping")
@@ -201,7 +474,18 @@ To get started, view the `start` help with `bartib start --help`")
                 .arg(&arg_yesterday)
                 .arg(&arg_current_week)
                 .arg(&arg_last_week)
+                .arg(&arg_current_month)
+                .arg(&arg_last_month)
+                .arg(&arg_current_year)
+                .arg(&arg_quarter)
+                .arg(&arg_last_days)
+                .arg(&arg_month)
+                .arg(&arg_week)
                 .arg(&arg_group)
+                .arg(&arg_round_strategy)
+                .arg(&arg_round_mode)
+                .arg(&arg_min_duration)
+                .arg(&arg_zone)
                 .arg(
                     Arg::with_name("project")
                         .short("p")
@@ -210,6 +494,118 @@ To get started, view the `start` help with `bartib start --help`")
                         .help("do report activities for this project only")
                         .takes_value(true)
                         .required(false),
+                )
+                .arg(&arg_tag_filter)
+                .arg(
+                    Arg::with_name("by_tag")
+                        .long("by_tag")
+                        .help("group the report by tags instead of projects")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("billing")
+                        .long("billing")
+                        .help("shows the billable amounts according to the hourly rates of the configuration file")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("balance")
+                .about("shows the target and actual working time per week and the resulting overtime")
+                .arg(&arg_from_date)
+                .arg(&arg_to_date)
+                .arg(
+                    Arg::with_name("project")
+                        .short("p")
+                        .long("project")
+                        .value_name("PROJECT")
+                        .help("count activities of this project only")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(&arg_tag_filter)
+                .arg(&arg_group)
+                .arg(&arg_round_strategy)
+                .arg(&arg_round_mode)
+                .arg(&arg_min_duration),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("exports tracked activities as JSON, JSON Lines or CSV")
+                .arg(&arg_from_date)
+                .arg(&arg_to_date)
+                .arg(&arg_date)
+                .arg(&arg_today)
+                .arg(&arg_yesterday)
+                .arg(&arg_current_week)
+                .arg(&arg_last_week)
+                .arg(&arg_current_month)
+                .arg(&arg_last_month)
+                .arg(&arg_current_year)
+                .arg(&arg_quarter)
+                .arg(&arg_last_days)
+                .arg(&arg_month)
+                .arg(&arg_week)
+                .arg(&arg_group)
+                .arg(&arg_round_strategy)
+                .arg(&arg_round_mode)
+                .arg(&arg_min_duration)
+                .arg(
+                    Arg::with_name("project")
+                        .short("p")
+                        .long("project")
+                        .value_name("PROJECT")
+                        .help("do export activities for this project only")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(&arg_tag_filter)
+                .arg(
+                    Arg::with_name("number")
+                        .short("n")
+                        .long("number")
+                        .value_name("NUMBER")
+                        .help("maximum number of activities to export")
+                        .required(false)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("the output format")
+                        .possible_values(&["json", "jsonl", "csv"])
+                        .default_value("json")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("imports activities from other time trackers")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("the format of the file to import")
+                        .possible_values(&["timewarrior", "watson", "toggl-csv", "bartib"])
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("dry_run")
+                        .long("dry-run")
+                        .help("only print the activities that would be imported")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .value_name("INPUT")
+                        .help("the file to import. Use - to read from stdin")
+                        .required(true)
+                        .takes_value(true),
                 ),
         )
         .subcommand(
@@ -281,49 +677,139 @@ To get started, view the `start` help with `bartib start --help`")
                         .help("show status for this project only")
                         .takes_value(true)
                         .required(false),
-                ),
+                )
+                .arg(&arg_tag_filter)
+                .arg(&arg_current_month)
+                .arg(&arg_last_month)
+                .arg(&arg_current_year)
+                .arg(&arg_quarter)
+                .arg(&arg_last_days)
+                .arg(&arg_month)
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("the output format. Ignored if --template is given")
+                        .possible_values(&["pretty", "json", "line", "waybar", "i3bar"])
+                        .default_value("pretty")
+                        .takes_value(true),
+                )
+                .arg(&arg_template),
         )
         .get_matches();
 
-    let file_name = matches.value_of("file")
-        .context("Please specify a file with your activity log either as -f option or as BARTIB_FILE environment variable")?;
+    let config = conf::Config::load()?;
+
+    format_util::set_colors_enabled(
+        !matches.is_present("no_color") && config.color.unwrap_or(true),
+    );
+    bartib_file::set_backups(config.get_backups());
+
+    let file_name = matches.value_of("file").or(config.file.as_deref())
+        .context("Please specify a file with your activity log either as -f option, as BARTIB_FILE environment variable or in the configuration file")?;
 
-    run_subcommand(&matches, file_name)
+    let subcommand = matches.subcommand_name().unwrap_or_default();
+    let _lock = lock_file(file_name, subcommand)?;
+
+    run_subcommand(&matches, file_name, &config)
 }
 
-fn run_subcommand(matches: &ArgMatches, file_name: &str) -> Result<()> {
+// locks the activity log for reading during a subcommand, so that it is not read while another
+// bartib process changes it. Subcommands that change the log lock it themselves
+fn lock_file(file_name: &str, subcommand: &str) -> Result<Option<bartib_file::FileLock>> {
+    if subcommand == "restore" || MANIPULATING_SUBCOMMANDS.contains(&subcommand) {
+        return Ok(None);
+    }
+
+    match bartib_file::lock(
+        file_name,
+        &bartib_file::LockKind::Shared,
+        conf::LOCK_TIMEOUT,
+    ) {
+        Ok(lock) => Ok(Some(lock)),
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => bail!(
+            "The file {file_name} is in use by another bartib process. Please try again later"
+        ),
+        // without a lock file the log has never been changed by bartib. A file that may only be
+        // read can still be read without a lock
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e).context(format!("Could not lock file: {file_name}")),
+    }
+}
+
+fn run_subcommand(matches: &ArgMatches, file_name: &str, config: &conf::Config) -> Result<()> {
     match matches.subcommand() {
         ("start", Some(sub_m)) => {
             let project_name = sub_m.value_of("project").unwrap();
             let activity_description = sub_m.value_of("description").unwrap();
-            let time = get_time_argument_or_ignore(sub_m.value_of("time"), "-t/--time")
-                .map(|t| Local::now().date_naive().and_time(t));
+            let tags = get_tags_argument(sub_m).unwrap_or_default();
+            let time = get_datetime_argument_or_ignore(sub_m.value_of("time"), "-t/--time");
 
             bartib::controller::manipulation::start(
                 file_name,
                 project_name,
                 activity_description,
+                &tags,
+                sub_m.value_of("note"),
                 time,
             )
         }
+        ("add", Some(sub_m)) => {
+            let project_name = sub_m.value_of("project").unwrap();
+            let activity_description = sub_m.value_of("description").unwrap();
+            let tags = get_tags_argument(sub_m).unwrap_or_default();
+            let start = get_datetime_argument_or_ignore(sub_m.value_of("start"), "-s/--start")
+                .context("Please specify a valid start of the activity")?;
+            let end =
+                match get_duration_argument_or_ignore(sub_m.value_of("duration"), "--duration") {
+                    Some(duration) => {
+                        // the end is calculated when the activity is added, as it depends on
+                        // changes of the clocks in between
+                        if start.checked_add_signed(duration).is_none() {
+                            bail!("The duration of the activity is too long");
+                        }
+                        Some(ActivityEnd::Duration(duration))
+                    }
+                    None => get_datetime_argument_or_ignore(sub_m.value_of("end"), "-e/--end")
+                        .map(ActivityEnd::Time),
+                }
+                .context("Please specify a valid end or duration of the activity")?;
+
+            bartib::controller::manipulation::add(
+                file_name,
+                project_name,
+                activity_description,
+                &tags,
+                start,
+                end,
+            )
+        }
         ("change", Some(sub_m)) => {
             let project_name = sub_m.value_of("project");
             let activity_description = sub_m.value_of("description");
-            let time = get_time_argument_or_ignore(sub_m.value_of("time"), "-t/--time")
-                .map(|t| Local::now().date_naive().and_time(t));
+            let tags = get_tags_argument(sub_m);
+            let time = get_datetime_argument_or_ignore(sub_m.value_of("time"), "-t/--time");
 
             bartib::controller::manipulation::change(
                 file_name,
                 project_name,
                 activity_description,
+                tags.as_deref(),
                 time,
             )
         }
         ("continue", Some(sub_m)) => {
             let project_name = sub_m.value_of("project");
             let activity_description = sub_m.value_of("description");
-            let time = get_time_argument_or_ignore(sub_m.value_of("time"), "-t/--time")
-                .map(|t| Local::now().date_naive().and_time(t));
+            let tags = get_tags_argument(sub_m);
+            let time = get_datetime_argument_or_ignore(sub_m.value_of("time"), "-t/--time");
             let number =
                 get_number_argument_or_ignore(sub_m.value_of("number"), "-n/--number").unwrap_or(0);
 
@@ -331,28 +817,146 @@ fn run_subcommand(matches: &ArgMatches, file_name: &str) -> Result<()> {
                 file_name,
                 project_name,
                 activity_description,
+                tags.as_deref(),
                 time,
                 number,
             )
         }
         ("stop", Some(sub_m)) => {
-            let time = get_time_argument_or_ignore(sub_m.value_of("time"), "-t/--time")
-                .map(|t| Local::now().date_naive().and_time(t));
+            let time = get_datetime_argument_or_ignore(sub_m.value_of("time"), "-t/--time");
 
             bartib::controller::manipulation::stop(file_name, time)
         }
+        ("pause", Some(sub_m)) => {
+            let time = get_datetime_argument_or_ignore(sub_m.value_of("time"), "-t/--time");
+
+            bartib::controller::manipulation::pause(file_name, time)
+        }
+        ("resume", Some(sub_m)) => {
+            let time = get_datetime_argument_or_ignore(sub_m.value_of("time"), "-t/--time");
+
+            bartib::controller::manipulation::resume(file_name, time)
+        }
         ("cancel", Some(_)) => bartib::controller::manipulation::cancel(file_name),
-        ("current", Some(_)) => bartib::controller::list::list_running(file_name),
+        ("amend", Some(sub_m)) => {
+            let index = get_index_argument(sub_m)?;
+            let project_name = sub_m.value_of("project");
+            let activity_description = sub_m.value_of("description");
+            let tags = get_tags_argument(sub_m);
+            let start = get_datetime_argument_or_ignore(sub_m.value_of("start"), "-s/--start");
+            let end = get_datetime_argument_or_ignore(sub_m.value_of("end"), "-e/--end");
+
+            bartib::controller::manipulation::amend(
+                file_name,
+                index,
+                project_name,
+                activity_description,
+                tags.as_deref(),
+                start,
+                end,
+            )
+        }
+        ("note", Some(sub_m)) => {
+            let index = match sub_m.value_of("index") {
+                Some(_) => Some(get_index_argument(sub_m)?),
+                None => None,
+            };
+            let notes = sub_m.value_of("notes").unwrap();
+            bartib::controller::manipulation::note(file_name, index, notes)
+        }
+        ("delete", Some(sub_m)) => {
+            let index = get_index_argument(sub_m)?;
+            bartib::controller::manipulation::delete(file_name, index)
+        }
+        ("undo", Some(_)) => bartib::controller::manipulation::undo(file_name),
+        ("redo", Some(_)) => bartib::controller::manipulation::redo(file_name),
+        ("restore", Some(sub_m)) => {
+            match get_number_argument_or_ignore(sub_m.value_of("number"), "NUMBER") {
+                Some(number) => bartib::controller::manipulation::restore(file_name, number),
+                None => bartib::controller::list::list_backups(file_name),
+            }
+        }
+        ("current", Some(sub_m)) => {
+            let template = get_template_argument(sub_m, &ACTIVITY_FIELDS)?;
+            bartib::controller::list::list_running(file_name, template.as_ref())
+        }
         ("list", Some(sub_m)) => {
-            let filter = create_filter_for_arguments(sub_m);
-            let processors = create_processors_for_arguments(sub_m);
+            let filter = create_filter_for_arguments(sub_m, config, None);
+            let processors = create_processors_for_arguments(sub_m, config, true)?;
             let do_group_activities = !sub_m.is_present("no_grouping") && filter.date.is_none();
-            bartib::controller::list::list(file_name, filter, do_group_activities, processors)
+            let template = get_template_argument(sub_m, &ACTIVITY_FIELDS)?;
+            bartib::controller::list::list(
+                file_name,
+                filter,
+                do_group_activities,
+                sub_m.is_present("verbose"),
+                processors,
+                template.as_ref(),
+            )
         }
         ("report", Some(sub_m)) => {
-            let filter = create_filter_for_arguments(sub_m);
-            let processors = create_processors_for_arguments(sub_m);
-            bartib::controller::report::show_report(file_name, filter, processors)
+            let filter = create_filter_for_arguments(sub_m, config, config.report_range.as_deref());
+            let rounding = get_rounding_for_arguments(sub_m, config);
+            let processors = create_processors(rounding, true, is_current_zone(sub_m, config));
+            let total_rounding = rounding
+                .filter(|(mode, _)| *mode == RoundMode::Totals)
+                .map(|(_, rounding)| rounding);
+            let grouping = if sub_m.is_present("by_tag") {
+                ReportGrouping::Tag
+            } else {
+                ReportGrouping::Project
+            };
+            let billing = if sub_m.is_present("billing") {
+                if config.rates.is_empty() {
+                    bail!("Please configure hourly rates in the configuration file to show billable amounts");
+                }
+
+                Some(Billing {
+                    rates: &config.rates,
+                    currency: config.currency.as_deref(),
+                })
+            } else {
+                None
+            };
+
+            bartib::controller::report::show_report(
+                file_name,
+                filter,
+                processors,
+                &grouping,
+                billing.as_ref(),
+                total_rounding.as_ref(),
+            )
+        }
+        ("balance", Some(sub_m)) => {
+            let filter = create_filter_for_arguments(sub_m, config, None);
+            let processors = create_processors_for_arguments(sub_m, config, true)?;
+            bartib::controller::balance::show_balance(file_name, filter, processors, config)
+        }
+        ("export", Some(sub_m)) => {
+            let filter = create_filter_for_arguments(sub_m, config, None);
+            let processors = create_processors_for_arguments(sub_m, config, false)?;
+            let format = match sub_m.value_of("format") {
+                Some("jsonl") => ExportFormat::JsonLines,
+                Some("csv") => ExportFormat::Csv,
+                _ => ExportFormat::Json,
+            };
+            bartib::controller::export::export(file_name, filter, processors, &format)
+        }
+        ("import", Some(sub_m)) => {
+            let input_file_name = sub_m.value_of("input").unwrap();
+            let format = match sub_m.value_of("format") {
+                Some("timewarrior") => ImportFormat::Timewarrior,
+                Some("watson") => ImportFormat::Watson,
+                Some("toggl-csv") => ImportFormat::TogglCsv,
+                _ => ImportFormat::Bartib,
+            };
+            bartib::controller::import::import(
+                file_name,
+                input_file_name,
+                &format,
+                sub_m.is_present("dry_run"),
+            )
         }
         ("projects", Some(sub_m)) => bartib::controller::list::list_projects(
             file_name,
@@ -365,7 +969,13 @@ fn run_subcommand(matches: &ArgMatches, file_name: &str) -> Result<()> {
             bartib::controller::list::list_last_activities(file_name, number)
         }
         ("edit", Some(sub_m)) => {
-            let optional_editor_command = sub_m.value_of("editor");
+            // an editor given on the command line takes precedence over the configuration file
+            // which itself takes precedence over the EDITOR environment variable
+            let optional_editor_command = if sub_m.occurrences_of("editor") > 0 {
+                sub_m.value_of("editor")
+            } else {
+                config.editor.as_deref().or(sub_m.value_of("editor"))
+            };
             bartib::controller::manipulation::start_editor(file_name, optional_editor_command)
         }
         ("check", Some(_)) => bartib::controller::list::check(file_name),
@@ -375,31 +985,181 @@ fn run_subcommand(matches: &ArgMatches, file_name: &str) -> Result<()> {
             bartib::controller::list::search(file_name, search_term)
         }
         ("status", Some(sub_m)) => {
-            let filter = create_filter_for_arguments(sub_m);
-            let processors = create_processors_for_arguments(sub_m);
-            let writer = create_status_writer(sub_m);
-            bartib::controller::status::show_status(file_name, filter, processors, writer.borrow())
+            let filter = create_filter_for_arguments(sub_m, config, None);
+            let processors = create_processors_for_arguments(sub_m, config, true)?;
+            let writer = create_status_writer(sub_m)?;
+            bartib::controller::status::show_status(
+                file_name,
+                filter,
+                processors,
+                writer.borrow(),
+                config,
+            )
         }
         _ => bail!("Unknown command"),
     }
 }
 
-fn create_processors_for_arguments(sub_m: &ArgMatches) -> processor::ProcessorList {
+// creates the processors for the arguments of a subcommand
+//
+// activities are split at midnight when they are summed up per day, so each day gets its share
+fn create_processors_for_arguments(
+    sub_m: &ArgMatches,
+    config: &conf::Config,
+    split_at_midnight: bool,
+) -> Result<processor::ProcessorList> {
+    let rounding = get_rounding_for_arguments(sub_m, config);
+    check_round_mode(rounding.as_ref())?;
+
+    Ok(create_processors(
+        rounding,
+        split_at_midnight,
+        is_current_zone(sub_m, config),
+    ))
+}
+
+// only reports have totals to round. Other subcommands reject the mode instead of silently
+// ignoring the rounding
+fn check_round_mode(rounding: Option<&(RoundMode, Rounding)>) -> Result<()> {
+    if let Some((RoundMode::Totals, _)) = rounding {
+        bail!("Totals can only be rounded in a report. Please use another --round_mode or set round_mode in the configuration file to \"timestamps\" or \"durations\"");
+    }
+
+    Ok(())
+}
+
+fn create_processors(
+    rounding: Option<(RoundMode, Rounding)>,
+    split_at_midnight: bool,
+    current_zone: bool,
+) -> processor::ProcessorList {
     let mut processors: Vec<Box<dyn processor::ActivityProcessor>> = Vec::new();
 
-    if let Some(round) = get_duration_argument_or_ignore(sub_m.value_of("round"), "--round") {
-        processors.push(Box::new(processor::RoundProcessor { round }));
+    // activities are moved to the current time zone before they are rounded or split by its days
+    if current_zone {
+        processors.push(Box::new(processor::CurrentZoneProcessor {}));
+    }
+
+    match rounding {
+        Some((RoundMode::Timestamps, rounding)) => {
+            if let Some(round) = rounding.round {
+                processors.push(Box::new(processor::RoundProcessor {
+                    round,
+                    strategy: rounding.strategy,
+                }));
+            }
+
+            // the minimum duration is applied after the timestamps have been rounded
+            if rounding.minimum.is_some() {
+                processors.push(Box::new(processor::RoundDurationProcessor {
+                    rounding: Rounding {
+                        round: None,
+                        ..rounding
+                    },
+                }));
+            }
+        }
+        Some((RoundMode::Durations, rounding)) => {
+            processors.push(Box::new(processor::RoundDurationProcessor { rounding }));
+        }
+        // totals are rounded by the report itself. Other subcommands reject this mode
+        Some((RoundMode::Totals, _)) | None => {}
+    }
+
+    if split_at_midnight {
+        processors.push(Box::new(processor::SplitAtMidnightProcessor {}));
     }
 
     processors
 }
 
-fn create_status_writer(_sub_m: &ArgMatches) -> Box<dyn processor::StatusReportWriter> {
-    let result = StatusReport {};
-    Box::new(result)
+// whether activities are shown in the current time zone instead of the time zones they have
+// been recorded in
+fn is_current_zone(sub_m: &ArgMatches, config: &conf::Config) -> bool {
+    sub_m.value_of("zone").or(config.zone.as_deref()) == Some("current")
+}
+
+// the rounding for the arguments of a subcommand. Returns None if there is nothing to round
+fn get_rounding_for_arguments(
+    sub_m: &ArgMatches,
+    config: &conf::Config,
+) -> Option<(RoundMode, Rounding)> {
+    let round = get_duration_argument_or_ignore(
+        sub_m.value_of("round").or(config.round.as_deref()),
+        "--round",
+    );
+    let minimum = get_duration_argument_or_ignore(
+        sub_m
+            .value_of("min_duration")
+            .or(config.min_duration.as_deref()),
+        "--min_duration",
+    );
+
+    if round.is_none() && minimum.is_none() {
+        return None;
+    }
+
+    let strategy = match sub_m
+        .value_of("round_strategy")
+        .or(config.round_strategy.as_deref())
+    {
+        Some("up") => RoundStrategy::Up,
+        Some("down") => RoundStrategy::Down,
+        _ => RoundStrategy::Nearest,
+    };
+
+    let mode = match sub_m
+        .value_of("round_mode")
+        .or(config.round_mode.as_deref())
+    {
+        Some("durations") => RoundMode::Durations,
+        Some("totals") => RoundMode::Totals,
+        _ => RoundMode::Timestamps,
+    };
+
+    Some((
+        mode,
+        Rounding {
+            round,
+            strategy,
+            minimum,
+        },
+    ))
+}
+
+fn create_status_writer(sub_m: &ArgMatches) -> Result<Box<dyn processor::StatusReportWriter>> {
+    if let Some(template) = get_template_argument(sub_m, &STATUS_FIELDS)? {
+        return Ok(Box::new(TemplateStatus { template }));
+    }
+
+    let writer: Box<dyn processor::StatusReportWriter> = match sub_m.value_of("format") {
+        Some("json") => Box::new(JsonStatus {}),
+        Some("line") => Box::new(LineStatus {}),
+        Some("waybar") => Box::new(WaybarStatus {}),
+        Some("i3bar") => Box::new(I3barStatus {}),
+        _ => Box::new(StatusReport {}),
+    };
+    Ok(writer)
+}
+
+fn get_template_argument(
+    sub_m: &ArgMatches,
+    fields: &[(&str, FieldKind)],
+) -> Result<Option<Template>> {
+    sub_m
+        .value_of("template")
+        .map(|template| Template::parse(template, fields))
+        .transpose()
 }
 
-fn create_filter_for_arguments<'a>(sub_m: &'a ArgMatches) -> ActivityFilter<'a> {
+// creates the filter for the arguments of a subcommand
+//
+// the default range is used when no date range has been given on the command line
+fn create_filter_for_arguments<'a>(
+    sub_m: &'a ArgMatches,
+    config: &conf::Config,
+    default_range: Option<&str>,
+) -> ActivityFilter<'a> {
     let mut filter = ActivityFilter {
         number_of_activities: get_number_argument_or_ignore(
             sub_m.value_of("number"),
@@ -409,41 +1169,109 @@ fn create_filter_for_arguments<'a>(sub_m: &'a ArgMatches) -> ActivityFilter<'a>
         to_date: get_date_argument_or_ignore(sub_m.value_of("to_date"), "--to"),
         date: get_date_argument_or_ignore(sub_m.value_of("date"), "-d/--date"),
         project: sub_m.value_of("project"),
+        tag: sub_m.value_of("tag"),
     };
 
+    let has_range_argument = RANGE_ARGUMENTS.iter().any(|name| sub_m.is_present(name));
     let today = Local::now().naive_local().date();
-    if sub_m.is_present("today") {
-        filter.date = Some(today);
-    }
 
-    if sub_m.is_present("yesterday") {
-        filter.date = Some(today - Duration::days(1));
-    }
+    let range = conf::REPORT_RANGES
+        .iter()
+        .find(|name| {
+            sub_m.is_present(name) || (!has_range_argument && default_range == Some(**name))
+        })
+        .and_then(|name| filter::get_named_range(name, today, config.get_week_start()))
+        .or_else(|| {
+            get_range_argument_or_ignore(sub_m.value_of("quarter"), "--quarter", |quarter| {
+                filter::parse_quarter(quarter, today)
+            })
+        })
+        .or_else(|| {
+            get_range_argument_or_ignore(sub_m.value_of("last_days"), "--last_days", |days| {
+                filter::get_last_days_range(today, days.parse().ok()?)
+            })
+        })
+        .or_else(|| {
+            get_range_argument_or_ignore(sub_m.value_of("month"), "--month", filter::parse_month)
+        })
+        .or_else(|| {
+            get_range_argument_or_ignore(sub_m.value_of("week"), "--week", |week| {
+                filter::parse_week(week, today, config.get_week_start())
+            })
+        });
 
-    if sub_m.is_present("current_week") {
-        filter.from_date =
-            Some(today - Duration::days(i64::from(today.weekday().num_days_from_monday())));
-        filter.to_date = Some(
-            today - Duration::days(i64::from(today.weekday().num_days_from_monday()))
-                + Duration::days(6),
-        );
+    match range {
+        // a single day is shown like a date given with --date
+        Some((from_date, to_date)) if from_date == to_date => filter.date = Some(from_date),
+        Some((from_date, to_date)) => {
+            filter.from_date = Some(from_date);
+            filter.to_date = Some(to_date);
+        }
+        None => {}
     }
 
-    if sub_m.is_present("last_week") {
-        filter.from_date = Some(
-            today
-                - Duration::days(i64::from(today.weekday().num_days_from_monday()))
-                - Duration::weeks(1),
-        );
-        filter.to_date = Some(
-            today
-                - Duration::days(i64::from(today.weekday().num_days_from_monday()))
-                - Duration::weeks(1)
-                + Duration::days(6),
-        )
+    filter
+}
+
+// all arguments that select the date range of a filter
+static RANGE_ARGUMENTS: [&str; 14] = [
+    "from_date",
+    "to_date",
+    "date",
+    "today",
+    "yesterday",
+    "current_week",
+    "last_week",
+    "current_month",
+    "last_month",
+    "current_year",
+    "quarter",
+    "last_days",
+    "month",
+    "week",
+];
+
+// the subcommands that change the activity log
+static MANIPULATING_SUBCOMMANDS: [&str; 15] = [
+    "start", "add", "change", "continue", "stop", "pause", "resume", "cancel", "amend", "delete",
+    "undo", "redo", "import", "edit", "note",
+];
+
+// the range arguments an argument conflicts with. --from and --to may be combined
+fn get_other_range_arguments(name: &str) -> Vec<&'static str> {
+    RANGE_ARGUMENTS
+        .iter()
+        .copied()
+        .filter(|other| *other != name)
+        .collect()
+}
+
+fn get_range_argument_or_ignore(
+    range_argument: Option<&str>,
+    argument_name: &str,
+    parse: impl Fn(&str) -> Option<(NaiveDate, NaiveDate)>,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let range_string = range_argument?;
+    let parsing_result = parse(range_string);
+
+    if parsing_result.is_none() {
+        println!("Can not parse \"{range_string}\" as date range. Argument for {argument_name} is ignored");
     }
 
-    filter
+    parsing_result
+}
+
+fn get_tags_argument(sub_m: &ArgMatches) -> Option<Vec<String>> {
+    sub_m
+        .values_of("tag")
+        .map(|values| activity::parse_tags(&values.collect::<Vec<&str>>().join(" ")))
+}
+
+fn get_index_argument(sub_m: &ArgMatches) -> Result<usize> {
+    let index_string = sub_m.value_of("index").unwrap();
+    index_string
+        .parse()
+        .context(format!("Can not parse \"{index_string}\" as index"))
 }
 
 fn get_number_argument_or_ignore(
@@ -471,38 +1299,33 @@ fn get_date_argument_or_ignore(
     argument_name: &str,
 ) -> Option<NaiveDate> {
     if let Some(date_string) = date_argument {
-        let parsing_result = NaiveDate::parse_from_str(date_string, bartib::conf::FORMAT_DATE);
-
-        match parsing_result {
-            Ok(date) => Some(date),
-            Err(parsing_error) => {
-                println!(
-                    "Can not parse \"{date_string}\" as date. Argument for {argument_name} is ignored ({parsing_error})"
-                );
-                None
-            }
+        let parsing_result = time_parser::parse_date(date_string, Local::now().naive_local());
+
+        if parsing_result.is_none() {
+            println!(
+                "Can not parse \"{date_string}\" as date. Argument for {argument_name} is ignored"
+            );
         }
+
+        parsing_result
     } else {
         None
     }
 }
 
-fn get_time_argument_or_ignore(
-    time_argument: Option<&str>,
+fn get_datetime_argument_or_ignore(
+    datetime_argument: Option<&str>,
     argument_name: &str,
-) -> Option<NaiveTime> {
-    if let Some(time_string) = time_argument {
-        let parsing_result = NaiveTime::parse_from_str(time_string, bartib::conf::FORMAT_TIME);
-
-        match parsing_result {
-            Ok(date) => Some(date),
-            Err(parsing_error) => {
-                println!(
-                    "Can not parse \"{time_string}\" as time. Argument for {argument_name} is ignored ({parsing_error})"
-                );
-                None
-            }
+) -> Option<NaiveDateTime> {
+    if let Some(datetime_string) = datetime_argument {
+        let parsing_result =
+            time_parser::parse_datetime(datetime_string, Local::now().naive_local());
+
+        if parsing_result.is_none() {
+            println!("Can not parse \"{datetime_string}\" as date and time. Argument for {argument_name} is ignored");
         }
+
+        parsing_result
     } else {
         None
     }
@@ -513,25 +1336,106 @@ fn get_duration_argument_or_ignore(
     argument_name: &str,
 ) -> Option<chrono::Duration> {
     if let Some(duration_string) = duration_argument {
-        // extract last character, leave the rest as number
-        let (number_string, duration_unit) = duration_string.split_at(duration_string.len() - 1);
-
-        let number: Option<i64> = number_string.parse().ok();
-
-        match number {
-            Some(number) => match duration_unit {
-                "m" => Some(chrono::Duration::minutes(number)),
-                "h" => Some(chrono::Duration::hours(number)),
-                _ => {
-                    println!(
-                            "Can not parse \"{duration_string}\" as duration. Argument for {argument_name} is ignored"
-                        );
-                    None
-                }
-            },
-            None => None,
+        let parsing_result = parse_duration(duration_string);
+
+        if parsing_result.is_none() {
+            println!(
+                "Can not parse \"{duration_string}\" as duration. Argument for {argument_name} is ignored"
+            );
+        }
+
+        parsing_result
+    } else {
+        None
+    }
+}
+
+// parses durations in minutes and hours, e.g. 15m, 4h or 1h30m
+fn parse_duration(duration_string: &str) -> Option<chrono::Duration> {
+    let mut duration = chrono::Duration::zero();
+    let mut number_string = String::new();
+
+    if duration_string.trim().is_empty() {
+        return None;
+    }
+
+    for c in duration_string.trim().chars() {
+        match c {
+            '0'..='9' => number_string.push(c),
+            'h' | 'm' => {
+                let number: i64 = number_string.parse().ok()?;
+                number_string.clear();
+
+                let unit_duration = if c == 'h' {
+                    chrono::Duration::try_hours(number)?
+                } else {
+                    chrono::Duration::try_minutes(number)?
+                };
+                duration = duration.checked_add(&unit_duration)?;
+            }
+            _ => return None,
         }
+    }
+
+    // a number without unit is not a valid duration
+    if number_string.is_empty() {
+        Some(duration)
     } else {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn check_round_mode_rejects_totals() {
+        let rounding = Rounding {
+            round: Some(Duration::minutes(15)),
+            strategy: RoundStrategy::Nearest,
+            minimum: None,
+        };
+
+        assert!(check_round_mode(None).is_ok());
+        assert!(check_round_mode(Some(&(RoundMode::Timestamps, rounding))).is_ok());
+        assert!(check_round_mode(Some(&(RoundMode::Durations, rounding))).is_ok());
+        assert!(check_round_mode(Some(&(RoundMode::Totals, rounding))).is_err());
+    }
+
+    #[test]
+    fn parse_duration_single_unit() {
+        assert_eq!(parse_duration("15m"), Some(Duration::minutes(15)));
+        assert_eq!(parse_duration("2h"), Some(Duration::hours(2)));
+        assert_eq!(parse_duration(" 4h "), Some(Duration::hours(4)));
+    }
+
+    #[test]
+    fn parse_duration_combined_units() {
+        assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("30m1h"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("1h1h"), Some(Duration::hours(2)));
+    }
+
+    #[test]
+    fn parse_duration_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("  "), None);
+        assert_eq!(parse_duration("15"), None);
+        assert_eq!(parse_duration("1h30"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("1d"), None);
+        assert_eq!(parse_duration("1h 30m"), None);
+        assert_eq!(parse_duration("-1h"), None);
+    }
+
+    #[test]
+    fn parse_duration_out_of_range() {
+        assert_eq!(parse_duration("99999999999999h"), None);
+        assert_eq!(parse_duration("9999999999999999m"), None);
+        assert_eq!(parse_duration("2000000000000h2000000000000h"), None);
+        assert_eq!(parse_duration("99999999999999999999h"), None);
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::Duration;
+use nu_ansi_term::{Color, Style};
+
+use crate::conf;
+use crate::data::working_time;
+use crate::view::format_util;
+use crate::view::table;
+
+// displays a table with the target and the actual working time of each period
+//
+// the last column shows the balance accumulated up to the end of each period
+pub fn show_balance(periods: &[working_time::Period]) {
+    if periods.is_empty() {
+        println!("No period to display");
+        return;
+    }
+
+    let mut balance_table = table::Table::new(vec![
+        table::Column {
+            label: "Period".to_string(),
+            wrap: table::Wrap::NoWrap,
+        },
+        table::Column {
+            label: "Target".to_string(),
+            wrap: table::Wrap::NoWrap,
+        },
+        table::Column {
+            label: "Actual".to_string(),
+            wrap: table::Wrap::NoWrap,
+        },
+        table::Column {
+            label: "Difference".to_string(),
+            wrap: table::Wrap::NoWrap,
+        },
+        table::Column {
+            label: "Balance".to_string(),
+            wrap: table::Wrap::NoWrap,
+        },
+    ]);
+
+    let mut balance = Duration::zero();
+
+    for period in periods {
+        balance += period.get_balance();
+        balance_table.add_row(get_period_row(period, balance));
+    }
+
+    println!("\n{balance_table}");
+
+    let color = if balance < Duration::zero() {
+        Color::Red
+    } else {
+        Color::Green
+    };
+    println!(
+        "Balance from {} to {}: {}",
+        periods[0].from.format(conf::FORMAT_DATE),
+        periods[periods.len() - 1].to.format(conf::FORMAT_DATE),
+        format_util::style(color.bold()).paint(format_util::format_balance(&balance))
+    );
+}
+
+fn get_period_row(period: &working_time::Period, balance: Duration) -> table::Row {
+    let mut row = table::Row::new(vec![
+        format!(
+            "{} - {}",
+            period.from.format(conf::FORMAT_DATE),
+            period.to.format(conf::FORMAT_DATE)
+        ),
+        format_util::format_exact_duration(&period.target),
+        format_util::format_exact_duration(&period.actual),
+        format_util::format_balance(&period.get_balance()),
+        format_util::format_balance(&balance),
+    ]);
+
+    if period.get_balance() < Duration::zero() {
+        row.set_color(Style::new().fg(Color::Red));
+    }
+
+    row
+}
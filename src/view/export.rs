@@ -0,0 +1,209 @@
+use serde_json::{json, Value};
+use std::io;
+use std::io::Write;
+
+use crate::conf;
+use crate::data::activity;
+
+// the formats in which activities can be exported
+pub enum ExportFormat {
+    Json,
+    JsonLines,
+    Csv,
+}
+
+static CSV_HEADER: &str = "start,end,duration,project,description,tags,notes,line";
+
+// prints activities together with the line numbers they have been read from to stdout
+pub fn export_activities(
+    activities: &[(Option<usize>, &activity::Activity)],
+    format: &ExportFormat,
+) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    write_activities(&mut handle, activities, format)
+}
+
+// writes activities in the given format
+pub fn write_activities<W: Write>(
+    w: &mut W,
+    activities: &[(Option<usize>, &activity::Activity)],
+    format: &ExportFormat,
+) -> io::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            writeln!(w, "[")?;
+            for (i, (line_number, activity)) in activities.iter().enumerate() {
+                let separator = if i + 1 < activities.len() { "," } else { "" };
+                writeln!(w, "  {}{separator}", to_json_object(activity, *line_number))?;
+            }
+            writeln!(w, "]")?;
+        }
+        ExportFormat::JsonLines => {
+            for (line_number, activity) in activities {
+                writeln!(w, "{}", to_json_object(activity, *line_number))?;
+            }
+        }
+        ExportFormat::Csv => {
+            writeln!(w, "{CSV_HEADER}")?;
+            for (line_number, activity) in activities {
+                writeln!(w, "{}", to_csv_row(activity, *line_number))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// the duration of an activity in seconds. Running activities are measured until now
+fn get_duration_in_seconds(activity: &activity::Activity) -> i64 {
+    activity.get_duration().num_seconds()
+}
+
+fn to_json_object(activity: &activity::Activity, line_number: Option<usize>) -> Value {
+    json!({
+        "start": activity.start.format(conf::FORMAT_EXPORT_DATETIME).to_string(),
+        "end": activity
+            .end
+            .map(|end| end.format(conf::FORMAT_EXPORT_DATETIME).to_string()),
+        "duration": get_duration_in_seconds(activity),
+        "project": activity.project,
+        "description": activity.description,
+        "tags": activity.tags,
+        "notes": activity.notes,
+        "line": line_number,
+    })
+}
+
+fn to_csv_row(activity: &activity::Activity, line_number: Option<usize>) -> String {
+    let cells = [
+        activity
+            .start
+            .format(conf::FORMAT_EXPORT_DATETIME)
+            .to_string(),
+        activity.end.map_or_else(String::new, |end| {
+            end.format(conf::FORMAT_EXPORT_DATETIME).to_string()
+        }),
+        get_duration_in_seconds(activity).to_string(),
+        activity.project.clone(),
+        activity.description.clone(),
+        activity.tags.join(" "),
+        activity.notes.clone().unwrap_or_default(),
+        line_number.map_or_else(String::new, |l| l.to_string()),
+    ];
+
+    cells
+        .iter()
+        .map(|c| csv_cell(c))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+// quotes a cell of a csv file if necessary (RFC 4180)
+fn csv_cell(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+
+    use super::*;
+
+    fn data() -> activity::Activity {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let mut a =
+            activity::Activity::start("p, 1".to_string(), "say \"hello\"".to_string(), Some(start));
+        a.end = Some(start + Duration::minutes(90));
+        a.tags = vec!["meeting".to_string()];
+        a.set_notes("see https://example.com/1, done");
+        a
+    }
+
+    fn write(format: &ExportFormat) -> String {
+        let a = data();
+        let mut buffer: Vec<u8> = Vec::new();
+        write_activities(&mut buffer, &[(Some(3), &a), (None, &a)], format).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn export_json_lines() {
+        let result = write(&ExportFormat::JsonLines);
+        let mut lines = result.lines();
+
+        let first: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(
+            first,
+            json!({
+                "start": "2024-03-01T09:00:00",
+                "end": "2024-03-01T10:30:00",
+                "duration": 5400,
+                "project": "p, 1",
+                "description": "say \"hello\"",
+                "tags": ["meeting"],
+                "notes": "see https://example.com/1, done",
+                "line": 3,
+            })
+        );
+
+        let second: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert!(second["line"].is_null());
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn export_json_running_activity() {
+        let mut a = data();
+        a.end = None;
+        a.notes = None;
+
+        let json = to_json_object(&a, None);
+        assert!(json["end"].is_null());
+        assert!(json["notes"].is_null());
+    }
+
+    #[test]
+    fn export_json() {
+        let result = write(&ExportFormat::Json);
+        let lines: Vec<&str> = result.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "[");
+        assert!(lines[1].starts_with("  {") && lines[1].ends_with("},"));
+        assert!(lines[2].starts_with("  {") && lines[2].ends_with('}'));
+        assert_eq!(lines[3], "]");
+    }
+
+    #[test]
+    fn export_csv() {
+        let result = write(&ExportFormat::Csv);
+        let mut lines = result.lines();
+
+        assert_eq!(lines.next().unwrap(), CSV_HEADER);
+        assert_eq!(
+            lines.next().unwrap(),
+            "2024-03-01T09:00:00,2024-03-01T10:30:00,5400,\"p, 1\",\"say \"\"hello\"\"\",meeting,\"see https://example.com/1, done\",3"
+        );
+        assert!(lines
+            .next()
+            .unwrap()
+            .ends_with(",meeting,\"see https://example.com/1, done\","));
+    }
+
+    #[test]
+    fn export_json_escapes_control_characters() {
+        let mut a = data();
+        a.description = "a\tb\u{1}".to_string();
+
+        let line = to_json_object(&a, None).to_string();
+        assert!(line.contains("\"description\":\"a\\tb\\u0001\""));
+    }
+}
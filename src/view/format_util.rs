@@ -1,4 +1,29 @@
 use chrono::Duration;
+use nu_ansi_term::Style;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLORS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+// enables or disables colors and other text styles for all views
+pub fn set_colors_enabled(enabled: bool) {
+    COLORS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+// whether colors and other text styles are enabled
+#[must_use]
+pub fn colors_enabled() -> bool {
+    COLORS_ENABLED.load(Ordering::Relaxed)
+}
+
+// returns the given style or a plain style if colors have been disabled
+#[must_use]
+pub fn style(style: Style) -> Style {
+    if colors_enabled() {
+        style
+    } else {
+        Style::new()
+    }
+}
 
 pub fn format_duration(duration: &Duration) -> String {
     let mut duration_string = String::new();
@@ -18,3 +43,27 @@ pub fn format_duration(duration: &Duration) -> String {
 
     duration_string
 }
+
+// formats a duration that may be exactly zero like a target without working hours
+//
+// contrary to format_duration a zero duration is shown as 0m instead of <1m
+#[must_use]
+pub fn format_exact_duration(duration: &Duration) -> String {
+    if duration.is_zero() {
+        "0m".to_string()
+    } else {
+        format_duration(duration)
+    }
+}
+
+// formats a duration that may be negative with its sign, e.g. +1h 30m or -15m
+#[must_use]
+pub fn format_balance(duration: &Duration) -> String {
+    if duration.is_zero() {
+        format_exact_duration(duration)
+    } else if *duration < Duration::zero() {
+        format!("-{}", format_duration(&-*duration))
+    } else {
+        format!("+{}", format_duration(duration))
+    }
+}
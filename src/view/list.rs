@@ -1,4 +1,4 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime};
 use nu_ansi_term::Color;
 use std::collections::BTreeMap;
 
@@ -7,45 +7,69 @@ use crate::data::activity;
 use crate::view::format_util;
 use crate::view::report;
 use crate::view::table;
+use crate::view::template::Template;
 
-// displays a table with activities
-pub fn list_activities(activities: &[&activity::Activity], with_start_dates: bool) {
+// displays a table with activities together with their indexes
+//
+// the index of an activity is the number of the line it has been read from
+pub fn list_activities(
+    activities: &[(Option<usize>, &activity::Activity)],
+    with_start_dates: bool,
+    with_notes: bool,
+) {
     if activities.is_empty() {
         println!("No activity to display");
         return;
     }
 
-    let mut activity_table = create_activity_table();
+    let mut activity_table = create_activity_table(with_notes);
 
     activities
         .iter()
-        .map(|t| get_activity_table_row(t, with_start_dates))
+        .map(|(index, t)| get_activity_table_row(*index, t, with_start_dates, with_notes))
         .for_each(|row| activity_table.add_row(row));
 
     println!("\n{activity_table}");
 }
 
+// prints each activity in a line of its own as given by a template
+pub fn list_activities_with_template(
+    activities: &[(Option<usize>, &activity::Activity)],
+    template: &Template,
+) {
+    for activity in activities {
+        println!("{}", template.render(activity));
+    }
+}
+
 // list activities grouped by the dates of their start time
-pub fn list_activities_grouped_by_date(activities: &[&activity::Activity]) {
+pub fn list_activities_grouped_by_date(
+    activities: &[(Option<usize>, &activity::Activity)],
+    with_notes: bool,
+) {
     if activities.is_empty() {
         println!("No activity to display");
         return;
     }
 
-    let mut activity_table = create_activity_table();
+    let mut activity_table = create_activity_table(with_notes);
 
     group_activities_by_date(activities)
         .iter()
         .map(|(date, activity_list)| {
-            create_activities_group(&format!("{date}"), activity_list.as_slice())
+            create_activities_group(&format!("{date}"), activity_list.as_slice(), with_notes)
         })
         .for_each(|g| activity_table.add_group(g));
 
     println!("\n{activity_table}");
 }
 
-fn create_activity_table() -> table::Table {
-    table::Table::new(vec![
+fn create_activity_table(with_notes: bool) -> table::Table {
+    let mut columns = vec![
+        table::Column {
+            label: " # ".to_string(),
+            wrap: table::Wrap::NoWrap,
+        },
         table::Column {
             label: "Started".to_string(),
             wrap: table::Wrap::NoWrap,
@@ -66,16 +90,34 @@ fn create_activity_table() -> table::Table {
             label: "Duration".to_string(),
             wrap: table::Wrap::NoWrap,
         },
-    ])
+    ];
+
+    if with_notes {
+        columns.push(table::Column {
+            label: "Notes".to_string(),
+            wrap: table::Wrap::Wrap,
+        });
+    }
+
+    table::Table::new(columns)
 }
 
-fn create_activities_group(title: &str, activities: &[&activity::Activity]) -> table::Group {
+fn create_activities_group(
+    title: &str,
+    activities: &[(Option<usize>, &activity::Activity)],
+    with_notes: bool,
+) -> table::Group {
     let rows = activities
         .iter()
-        .map(|a| get_activity_table_row(a, false))
+        .map(|(index, a)| get_activity_table_row(*index, a, false, with_notes))
         .collect();
 
-    let total_duration = report::sum_duration(activities);
+    let total_duration = report::sum_duration(
+        &activities
+            .iter()
+            .map(|(_, a)| *a)
+            .collect::<Vec<&activity::Activity>>(),
+    );
 
     table::Group::new(
         Some(format!(
@@ -177,22 +219,20 @@ pub fn list_descriptions_and_projects_with_index(
 
 // create a row for a activity
 //
-// the date of the end is shown when it is not the same date as the start
-fn get_activity_table_row(activity: &activity::Activity, with_start_dates: bool) -> table::Row {
-    let more_then_one_day = activity
-        .end
-        .is_some_and(|end| activity.start.date() != end.date());
-
-    let display_end = activity.end.map_or_else(
-        || "-".to_string(),
-        |end| {
-            if more_then_one_day {
-                end.format(conf::FORMAT_DATETIME).to_string()
-            } else {
-                end.format(conf::FORMAT_TIME).to_string()
-            }
-        },
-    );
+// the date of the end is shown when it is not the same date as the start. The segments of a
+// paused and resumed activity are marked at the pause and at the resumption
+fn get_activity_table_row(
+    index: Option<usize>,
+    activity: &activity::Activity,
+    with_start_dates: bool,
+    with_notes: bool,
+) -> table::Row {
+    let more_then_one_day = ends_on_another_day(activity);
+    let mut display_end = format_end(activity);
+
+    if activity.paused {
+        display_end.push_str(" (paused)");
+    }
 
     let start_format = if with_start_dates {
         conf::FORMAT_DATETIME
@@ -200,13 +240,26 @@ fn get_activity_table_row(activity: &activity::Activity, with_start_dates: bool)
         conf::FORMAT_TIME
     };
 
-    let mut new_row = table::Row::new(vec![
-        activity.start.format(start_format).to_string(),
+    let mut display_start = activity.start.format(start_format).to_string();
+
+    if activity.resumed {
+        display_start.push_str(" (resumed)");
+    }
+
+    let mut cells = vec![
+        index.map_or_else(String::new, |i| format!("[{i}]")),
+        display_start,
         display_end,
         activity.description.clone(),
         activity.project.clone(),
         format_util::format_duration(&activity.get_duration()),
-    ]);
+    ];
+
+    if with_notes {
+        cells.push(activity.notes.clone().unwrap_or_default());
+    }
+
+    let mut new_row = table::Row::new(cells);
 
     if !activity.is_stopped() {
         new_row.set_color(Color::Green.normal());
@@ -217,22 +270,88 @@ fn get_activity_table_row(activity: &activity::Activity, with_start_dates: bool)
     new_row
 }
 
+// checks whether an activity ends on another day than it started
+//
+// an end at the midnight following the start, e.g. of a part split at midnight, still belongs to
+// the day of the start
+fn ends_on_another_day(activity: &activity::Activity) -> bool {
+    activity
+        .end
+        .is_some_and(|end| activity.start.date() != end.date() && !is_next_midnight(activity))
+}
+
+fn is_next_midnight(activity: &activity::Activity) -> bool {
+    activity.end.is_some_and(|end| {
+        activity.start.date().succ_opt() == Some(end.date()) && end.time() == NaiveTime::MIN
+    })
+}
+
+// formats the end of an activity as shown in the list
+fn format_end(activity: &activity::Activity) -> String {
+    match activity.end {
+        None => "-".to_string(),
+        Some(end) if ends_on_another_day(activity) => end.format(conf::FORMAT_DATETIME).to_string(),
+        // the following midnight is shown as the end of the day of the start
+        Some(end) if is_next_midnight(activity) => end
+            .format(conf::FORMAT_TIME)
+            .to_string()
+            .replacen("00", "24", 1),
+        Some(end) => end.format(conf::FORMAT_TIME).to_string(),
+    }
+}
+
 // groups activities in vectors of activities that started at the same day
 fn group_activities_by_date<'a>(
-    activities: &[&'a activity::Activity],
-) -> BTreeMap<NaiveDate, Vec<&'a activity::Activity>> {
+    activities: &[(Option<usize>, &'a activity::Activity)],
+) -> BTreeMap<NaiveDate, Vec<(Option<usize>, &'a activity::Activity)>> {
     let mut activities_by_date = BTreeMap::new();
 
-    for &activity in activities {
+    for &(index, activity) in activities {
         activities_by_date
             .entry(activity.start.date())
             .or_insert_with(Vec::new)
-            .push(activity);
+            .push((index, activity));
     }
 
     for activity_list in activities_by_date.values_mut() {
-        activity_list.sort_by_key(|activity| activity.start);
+        activity_list.sort_by_key(|(_, activity)| activity.start);
     }
 
     activities_by_date
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::data::processor::{ActivityProcessor, SplitAtMidnightProcessor};
+
+    #[test]
+    fn format_end_of_split_activity() {
+        let a = activity::Activity::from_str("2024-03-01 22:00 - 2024-03-02 02:00 | p1 | late")
+            .unwrap();
+        assert!(ends_on_another_day(&a));
+        assert!(format_end(&a).starts_with("2024-03-02 02:00"));
+
+        let parts = SplitAtMidnightProcessor {}.process(&a);
+        assert_eq!(parts.len(), 2);
+
+        assert!(!ends_on_another_day(&parts[0]));
+        assert!(format_end(&parts[0]).starts_with("24:00"));
+        assert!(!ends_on_another_day(&parts[1]));
+        assert!(format_end(&parts[1]).starts_with("02:00"));
+    }
+
+    #[test]
+    fn format_end_at_later_midnight() {
+        let a = activity::Activity::from_str("2024-03-01 22:00 - 2024-03-03 00:00 | p1 | long")
+            .unwrap();
+        assert!(ends_on_another_day(&a));
+        assert!(format_end(&a).starts_with("2024-03-03 00:00"));
+
+        let running = activity::Activity::from_str("2024-03-01 22:00 | p1 | running").unwrap();
+        assert!(!ends_on_another_day(&running));
+        assert_eq!(format_end(&running), "-");
+    }
+}
@@ -1,5 +1,8 @@
+pub mod balance;
+pub mod export;
 pub mod format_util;
 pub mod list;
 pub mod report;
 pub mod status;
 pub mod table;
+pub mod template;
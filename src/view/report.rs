@@ -9,22 +9,71 @@ use textwrap;
 
 use crate::conf;
 use crate::data::activity;
+use crate::data::billing;
 use crate::view::format_util;
 
 type ProjectMap<'a> = BTreeMap<&'a str, (Vec<&'a activity::Activity>, Duration)>;
 
+// the heading under which activities without any tag are reported
+static UNTAGGED: &str = "(untagged)";
+
+// the criterion by which the activities of a report are grouped
+pub enum ReportGrouping {
+    Project,
+    Tag,
+}
+
+// the hourly rates and the currency by which the billable amounts of a report are calculated
+pub struct Billing<'a> {
+    pub rates: &'a [conf::HourlyRate],
+    pub currency: Option<&'a str>,
+}
+
+impl Billing<'_> {
+    // formats the billable amount of activities. Activities that are not billed result in a
+    // placeholder, so the amounts stay aligned
+    fn format_amount(&self, activities: &[&activity::Activity]) -> String {
+        billing::get_amount(self.rates, activities).map_or_else(
+            || "-".to_string(),
+            |amount| match self.currency {
+                Some(currency) => format!("{amount:.2} {currency}"),
+                None => format!("{amount:.2}"),
+            },
+        )
+    }
+}
+
 struct Report<'a> {
+    activities: &'a [&'a activity::Activity],
     project_map: ProjectMap<'a>,
     total_duration: Duration,
+    billing: Option<&'a Billing<'a>>,
 }
 
 impl<'a> Report<'a> {
-    fn new(activities: &'a [&'a activity::Activity]) -> Report<'a> {
+    fn new(
+        activities: &'a [&'a activity::Activity],
+        grouping: &ReportGrouping,
+        billing: Option<&'a Billing<'a>>,
+    ) -> Report<'a> {
+        let project_map = match grouping {
+            ReportGrouping::Project => create_project_map(activities),
+            ReportGrouping::Tag => create_tag_map(activities),
+        };
+
         Report {
-            project_map: create_project_map(activities),
+            activities,
+            project_map,
             total_duration: sum_duration(activities),
+            billing,
         }
     }
+
+    // the formatted billable amount of activities or an empty string without billing
+    fn format_amount(&self, activities: &[&activity::Activity]) -> String {
+        self.billing
+            .map_or_else(String::new, |billing| billing.format_amount(activities))
+    }
 }
 
 impl fmt::Display for Report<'_> {
@@ -38,26 +87,50 @@ impl fmt::Display for Report<'_> {
             longest_line = terminal_width - longest_duration_string - 1;
         }
 
+        let amount_width = get_longest_amount_string(self);
+
         for (project, (activities, duration)) in &self.project_map {
             print_project_heading(f, project, duration, longest_line, longest_duration_string)?;
+            print_amount(f, &self.format_amount(activities), amount_width)?;
+            writeln!(
+                f,
+                "{}",
+                format_util::style(Style::new().bold()).infix(Style::new())
+            )?;
 
             print_descriptions_with_durations(
                 f,
+                self,
                 activities,
                 longest_line,
-                longest_duration_string,
+                (longest_duration_string, amount_width),
             )?;
             writeln!(f)?;
         }
 
-        print_total_duration(f, self.total_duration, longest_line)?;
+        print_total_duration(
+            f,
+            self.total_duration,
+            longest_line,
+            longest_duration_string,
+        )?;
+        print_amount(f, &self.format_amount(self.activities), amount_width)?;
+        writeln!(
+            f,
+            "{}",
+            format_util::style(Style::new().bold()).infix(Style::new())
+        )?;
 
         Ok(())
     }
 }
 
-pub fn show_activities<'a>(activities: &'a [&'a activity::Activity]) {
-    let report = Report::new(activities);
+pub fn show_activities<'a>(
+    activities: &'a [&'a activity::Activity],
+    grouping: &ReportGrouping,
+    billing: Option<&'a Billing<'a>>,
+) {
+    let report = Report::new(activities, grouping, billing);
     println!("\n{report}");
 }
 
@@ -79,6 +152,36 @@ fn create_project_map<'a>(activities: &'a [&'a activity::Activity]) -> ProjectMa
     project_map
 }
 
+// groups activities by their tags
+//
+// an activity with several tags is listed under each of them, so the durations of the groups may
+// add up to more than the total duration
+fn create_tag_map<'a>(activities: &'a [&'a activity::Activity]) -> ProjectMap<'a> {
+    let mut tag_map: ProjectMap = BTreeMap::new();
+
+    for a in activities {
+        let tags: Vec<&str> = if a.tags.is_empty() {
+            vec![UNTAGGED]
+        } else {
+            a.tags.iter().map(String::as_str).collect()
+        };
+
+        for tag in tags {
+            tag_map
+                .entry(tag)
+                .or_insert_with(|| (Vec::<&'a activity::Activity>::new(), Duration::seconds(0)))
+                .0
+                .push(a);
+        }
+    }
+
+    for (activities, duration) in tag_map.values_mut() {
+        *duration = sum_duration(activities);
+    }
+
+    tag_map
+}
+
 pub fn sum_duration(activities: &[&activity::Activity]) -> Duration {
     let mut duration = Duration::seconds(0);
 
@@ -96,7 +199,7 @@ fn print_project_heading(
     longest_line: usize,
     duration_width: usize,
 ) -> fmt::Result {
-    write!(f, "{}", Style::new().bold().prefix())?;
+    write!(f, "{}", format_util::style(Style::new().bold()).prefix())?;
     let project_lines = textwrap::wrap(project, textwrap::Options::new(longest_line));
 
     for (i, line) in project_lines.iter().enumerate() {
@@ -114,14 +217,24 @@ fn print_project_heading(
         }
     }
 
-    writeln!(f, "{}", Style::new().bold().infix(Style::new()))
+    Ok(())
+}
+
+// the amount is printed in an additional column if the report has billing
+fn print_amount(f: &mut fmt::Formatter<'_>, amount: &str, amount_width: usize) -> fmt::Result {
+    if !amount.is_empty() {
+        write!(f, " {amount:>amount_width$}")?;
+    }
+
+    Ok(())
 }
 
 fn print_descriptions_with_durations<'a>(
     f: &mut fmt::Formatter<'_>,
+    report: &Report,
     activities: &'a [&'a activity::Activity],
     line_width: usize,
-    duration_width: usize,
+    (duration_width, amount_width): (usize, usize),
 ) -> fmt::Result {
     let description_map = group_activities_by_description(activities);
     let indent_string = " ".repeat(conf::REPORT_INDENTATION);
@@ -137,7 +250,7 @@ fn print_descriptions_with_durations<'a>(
             if i + 1 < description_lines.len() {
                 writeln!(f, "{line}")?;
             } else {
-                writeln!(
+                write!(
                     f,
                     "{line:.<width$} {duration:>duration_width$}",
                     line = line,
@@ -145,6 +258,8 @@ fn print_descriptions_with_durations<'a>(
                     duration = format_util::format_duration(&description_duration),
                     duration_width = duration_width
                 )?;
+                print_amount(f, &report.format_amount(activities), amount_width)?;
+                writeln!(f)?;
             }
         }
     }
@@ -156,18 +271,17 @@ fn print_total_duration(
     f: &mut fmt::Formatter<'_>,
     total_duration: Duration,
     line_width: usize,
+    duration_width: usize,
 ) -> fmt::Result {
-    writeln!(
+    write!(
         f,
-        "{prefix}{total:.<width$} {duration}{suffix}",
-        prefix = Style::new().bold().prefix(),
+        "{prefix}{total:.<width$} {duration:>duration_width$}",
+        prefix = format_util::style(Style::new().bold()).prefix(),
         total = "Total",
         width = line_width,
         duration = format_util::format_duration(&total_duration),
-        suffix = Style::new().bold().infix(Style::new())
-    )?;
-
-    Ok(())
+        duration_width = duration_width
+    )
 }
 
 fn group_activities_by_description<'a>(
@@ -216,6 +330,26 @@ fn get_longest_duration_string(report: &Report) -> Option<usize> {
     get_max_option(longest_single_duration, Some(length_of_total_duration))
 }
 
+// the width of the column with the billable amounts. Zero if the report has no billing
+fn get_longest_amount_string(report: &Report) -> usize {
+    let project_amounts = report
+        .project_map
+        .values()
+        .map(|(activities, _d)| report.format_amount(activities));
+    let description_amounts = report
+        .project_map
+        .values()
+        .flat_map(|(activities, _d)| group_activities_by_description(activities).into_values())
+        .map(|activities| report.format_amount(&activities));
+
+    project_amounts
+        .chain(description_amounts)
+        .chain([report.format_amount(report.activities)])
+        .map(|s| s.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
 fn get_max_option(o1: Option<usize>, o2: Option<usize>) -> Option<usize> {
     if let Some(s1) = o1 {
         if let Some(s2) = o2 {
@@ -295,6 +429,23 @@ mod tests {
         assert_eq!(m.get("p2").unwrap().0.len(), 1);
     }
 
+    #[test]
+    fn group_activities_by_tag_test() {
+        let mut a1 = activity::Activity::start("p1".to_string(), "d1".to_string(), None);
+        a1.tags = vec!["meeting".to_string(), "review".to_string()];
+        let mut a2 = activity::Activity::start("p1".to_string(), "d2".to_string(), None);
+        a2.tags = vec!["meeting".to_string()];
+        let a3 = activity::Activity::start("p2".to_string(), "d1".to_string(), None);
+
+        let activities = vec![&a1, &a2, &a3];
+        let m = create_tag_map(&activities);
+
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.get("meeting").unwrap().0.len(), 2);
+        assert_eq!(m.get("review").unwrap().0.len(), 1);
+        assert_eq!(m.get(UNTAGGED).unwrap().0.len(), 1);
+    }
+
     #[test]
     fn group_activities_by_description_test() {
         let a1 = activity::Activity::start("p1".to_string(), "d1".to_string(), None);
@@ -350,4 +501,46 @@ mod tests {
         assert_eq!(get_max_option(Some(2), Some(1)).unwrap(), 2);
         assert_eq!(get_max_option(Some(1), Some(2)).unwrap(), 2);
     }
+
+    #[test]
+    fn display_billing() {
+        let start = NaiveDateTime::parse_from_str("2021-09-01 15:00", "%Y-%m-%d %H:%M").unwrap();
+        let mut a1 =
+            activity::Activity::start("client-a".to_string(), "d1".to_string(), Some(start));
+        a1.end = Some(start + Duration::minutes(60));
+        let mut a2 =
+            activity::Activity::start("internal".to_string(), "d2".to_string(), Some(start));
+        a2.end = Some(start + Duration::minutes(30));
+
+        let rates = vec![conf::HourlyRate {
+            project: "client-*".to_string(),
+            rate: 90.0,
+        }];
+        let billing = Billing {
+            rates: &rates,
+            currency: Some("EUR"),
+        };
+        let activities = vec![&a1, &a2];
+        let report = Report::new(&activities, &ReportGrouping::Project, Some(&billing));
+
+        let output = format!("{report}");
+        let plain: String = output
+            .split('\u{1b}')
+            .enumerate()
+            .map(|(i, part)| {
+                if i == 0 {
+                    part
+                } else {
+                    &part[part.find('m').unwrap() + 1..]
+                }
+            })
+            .collect();
+
+        assert_eq!(
+            plain,
+            "client-a 1h 00m 90.00 EUR\n    d1.. 1h 00m 90.00 EUR\n\n\
+             internal    30m         -\n    d2..    30m         -\n\n\
+             Total... 1h 30m 90.00 EUR\n"
+        );
+    }
 }
@@ -2,10 +2,13 @@ use std::fmt;
 
 use chrono::Duration;
 use nu_ansi_term::{Color, Style};
+use serde_json::{json, Value};
 
+use crate::conf;
 use crate::data::activity;
 use crate::data::processor::{StatusReportData, StatusReportWriter};
 use crate::view::format_util;
+use crate::view::template::Template;
 
 pub struct StatusReport {}
 
@@ -16,14 +19,225 @@ impl StatusReportWriter for StatusReport {
     }
 }
 
+// prints the status as a single JSON object
+pub struct JsonStatus {}
+
+impl StatusReportWriter for JsonStatus {
+    fn process(&self, data: &StatusReportData) -> anyhow::Result<()> {
+        println!("{}", to_json(data));
+        Ok(())
+    }
+}
+
+// prints the status as a single line of plain text, e.g. for shell prompts
+pub struct LineStatus {}
+
+impl StatusReportWriter for LineStatus {
+    fn process(&self, data: &StatusReportData) -> anyhow::Result<()> {
+        println!("{}", to_line(data));
+        Ok(())
+    }
+}
+
+// prints the status in the format of a custom waybar module with `return-type: json`
+pub struct WaybarStatus {}
+
+impl StatusReportWriter for WaybarStatus {
+    fn process(&self, data: &StatusReportData) -> anyhow::Result<()> {
+        println!("{}", to_waybar(data));
+        Ok(())
+    }
+}
+
+// prints the status as a single block in the format of the i3bar protocol, e.g. for i3blocks
+// or py3status. The header and the endless array of the full protocol are not printed
+pub struct I3barStatus {}
+
+impl StatusReportWriter for I3barStatus {
+    fn process(&self, data: &StatusReportData) -> anyhow::Result<()> {
+        println!("{}", to_i3bar(data));
+        Ok(())
+    }
+}
+
+// prints the status with a custom template
+pub struct TemplateStatus {
+    pub template: Template,
+}
+
+impl StatusReportWriter for TemplateStatus {
+    fn process(&self, data: &StatusReportData) -> anyhow::Result<()> {
+        println!("{}", self.template.render(data));
+        Ok(())
+    }
+}
+
+fn activity_to_json(activity: &activity::Activity) -> Value {
+    json!({
+        "project": activity.project,
+        "description": activity.description,
+        "tags": activity.tags,
+        "start": activity.start.format(conf::FORMAT_EXPORT_DATETIME).to_string(),
+        "duration": activity.get_duration().num_seconds(),
+    })
+}
+
+// all durations are given in seconds
+fn to_json(data: &StatusReportData) -> Value {
+    let targets = data.targets.as_ref().map(|targets| {
+        json!({
+            "today": targets.today.num_seconds(),
+            "current_week": targets.current_week.num_seconds(),
+            "current_month": targets.current_month.num_seconds(),
+            "balance": targets.balance.map(|balance| balance.num_seconds()),
+        })
+    });
+
+    json!({
+        "state": data.get_state(),
+        "project": data.project,
+        "activity": data.activity.map(activity_to_json),
+        "paused": data.paused.map(activity_to_json),
+        "today": data.today.num_seconds(),
+        "current_week": data.current_week.num_seconds(),
+        "current_month": data.current_month.num_seconds(),
+        "range": data.range.map(|range| range.num_seconds()),
+        "targets": targets,
+    })
+}
+
+// a short description of the current activity, e.g. `olia on project 10m`
+fn get_activity_text(data: &StatusReportData) -> String {
+    match (data.activity, data.paused) {
+        (Some(activity), _) => format!(
+            "{} on {} {}",
+            activity.description,
+            activity.project,
+            format_util::format_duration(&activity.get_duration())
+        ),
+        (None, Some(paused)) => format!("paused: {} on {}", paused.description, paused.project),
+        (None, None) => "no activity".to_string(),
+    }
+}
+
+fn get_duration_text(duration: Duration, target: Option<Duration>) -> String {
+    match target {
+        Some(target) => format!(
+            "{} / {}",
+            format_util::format_duration(&duration),
+            format_util::format_exact_duration(&target)
+        ),
+        None => format_util::format_duration(&duration),
+    }
+}
+
+fn to_line(data: &StatusReportData) -> String {
+    format!(
+        "{} | today {}",
+        get_activity_text(data),
+        get_duration_text(data.today, data.targets.as_ref().map(|t| t.today))
+    )
+}
+
+// the totals of all periods, one per line
+fn get_tooltip(data: &StatusReportData) -> String {
+    let targets = data.targets.as_ref();
+    let mut lines = vec![
+        format!(
+            "Today: {}",
+            get_duration_text(data.today, targets.map(|t| t.today))
+        ),
+        format!(
+            "Current week: {}",
+            get_duration_text(data.current_week, targets.map(|t| t.current_week))
+        ),
+        format!(
+            "Current month: {}",
+            get_duration_text(data.current_month, targets.map(|t| t.current_month))
+        ),
+    ];
+
+    if let Some(balance) = targets.and_then(|t| t.balance) {
+        lines.push(format!(
+            "Balance: {}",
+            format_util::format_balance(&balance)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+// waybar interprets texts as pango markup
+fn to_waybar(data: &StatusReportData) -> Value {
+    json!({
+        "text": escape_markup(&to_line(data)),
+        "alt": data.get_state(),
+        "class": data.get_state(),
+        "tooltip": escape_markup(&get_tooltip(data)),
+    })
+}
+
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn to_i3bar(data: &StatusReportData) -> Value {
+    let mut block = json!({
+        "name": "bartib",
+        "full_text": to_line(data),
+        "short_text": get_activity_text(data),
+    });
+
+    let color = match data.get_state() {
+        "active" => Some("#00ff00"),
+        "paused" => Some("#ffff00"),
+        _ => None,
+    };
+
+    if let Some(color) = color.filter(|_| format_util::colors_enabled()) {
+        block["color"] = json!(color);
+    }
+
+    block
+}
+
 impl fmt::Display for StatusReportData<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let longest_line = 30;
         print_title(f, self.project)?;
-        print_activity(f, self.activity, self.project)?;
-        print_duration(f, "Today", self.today, longest_line)?;
-        print_duration(f, "Current week", self.current_week, longest_line)?;
-        print_duration(f, "Current month", self.current_month, longest_line)?;
+        print_activity(f, self.activity, self.paused, self.project)?;
+        let targets = self.targets.as_ref();
+        print_duration(
+            f,
+            "Today",
+            self.today,
+            targets.map(|t| t.today),
+            longest_line,
+        )?;
+        print_duration(
+            f,
+            "Current week",
+            self.current_week,
+            targets.map(|t| t.current_week),
+            longest_line,
+        )?;
+        print_duration(
+            f,
+            "Current month",
+            self.current_month,
+            targets.map(|t| t.current_month),
+            longest_line,
+        )?;
+
+        if let Some(range) = self.range {
+            print_duration(f, "Selected range", range, None, longest_line)?;
+        }
+
+        if let Some(balance) = targets.and_then(|t| t.balance) {
+            print_balance(f, balance, longest_line)?;
+        }
 
         Ok(())
     }
@@ -33,6 +247,7 @@ fn print_duration(
     f: &mut fmt::Formatter<'_>,
     name: &str,
     total_duration: Duration,
+    target: Option<Duration>,
     line_width: usize,
 ) -> fmt::Result {
     write(f, " ", Style::new().italic())?;
@@ -42,6 +257,35 @@ fn print_duration(
         format_util::format_duration(&total_duration).as_str(),
         Style::new().bold(),
     )?;
+
+    if let Some(target) = target {
+        write(f, " / ", Style::new().dimmed())?;
+        write(
+            f,
+            format_util::format_exact_duration(&target).as_str(),
+            Style::new().dimmed(),
+        )?;
+    }
+
+    write(f, "\n", Style::new().italic())?;
+    Ok(())
+}
+
+// the overtime is shown in green and missing hours in red
+fn print_balance(f: &mut fmt::Formatter<'_>, balance: Duration, line_width: usize) -> fmt::Result {
+    let color = if balance < Duration::zero() {
+        Color::Red
+    } else {
+        Color::Green
+    };
+
+    write(f, " ", Style::new().italic())?;
+    write_period(f, "Balance", line_width, Style::new().italic().dimmed())?;
+    write(
+        f,
+        format_util::format_balance(&balance).as_str(),
+        color.bold(),
+    )?;
     write(f, "\n", Style::new().italic())?;
     Ok(())
 }
@@ -49,6 +293,7 @@ fn print_duration(
 fn print_activity(
     f: &mut fmt::Formatter<'_>,
     activity: Option<&activity::Activity>,
+    paused: Option<&activity::Activity>,
     project: Option<&str>,
 ) -> fmt::Result {
     match activity {
@@ -67,10 +312,21 @@ fn print_activity(
             )?;
             write(f, "\n\n", Style::new().dimmed())?;
         }
-        None => {
-            write(f, "\n  NOW: ", Style::new().italic().dimmed())?;
-            write(f, " NO Activity\n\n", Style::new().bold())?;
-        }
+        None => match paused {
+            Some(paused) => {
+                write(f, "\n  PAUSED: ", Style::new().italic().dimmed())?;
+                write(f, paused.description.as_str(), Color::Yellow.bold())?;
+                if project.is_none() {
+                    write(f, " on ", Style::new().italic().dimmed())?;
+                    write(f, &paused.project, Style::new().italic())?;
+                };
+                write(f, "\n\n", Style::new().dimmed())?;
+            }
+            None => {
+                write(f, "\n  NOW: ", Style::new().italic().dimmed())?;
+                write(f, " NO Activity\n\n", Style::new().bold())?;
+            }
+        },
     }
     Ok(())
 }
@@ -94,6 +350,7 @@ fn print_title(f: &mut fmt::Formatter<'_>, project: Option<&str>) -> fmt::Result
 }
 
 fn write(f: &mut fmt::Formatter<'_>, text: &str, style: Style) -> fmt::Result {
+    let style = format_util::style(style);
     write!(
         f,
         "{prefix}{text}{suffix}",
@@ -109,6 +366,7 @@ fn write_period(
     line_width: usize,
     style: Style,
 ) -> fmt::Result {
+    let style = format_util::style(style);
     write!(
         f,
         "{prefix} {text:.<line_width$} {suffix}",
@@ -123,15 +381,19 @@ mod tests {
     use chrono::Local;
 
     use super::*;
+    use crate::data::processor::StatusTargets;
 
     #[test]
     fn report_test() {
         let data = StatusReportData {
             activity: None,
+            paused: None,
             project: None,
             current_month: Duration::hours(10),
+            range: None,
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            targets: None,
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for <>[1mALL<>[3m projects <>[2m ======= 
@@ -148,14 +410,50 @@ mod tests {
         assert_eq!(clean(res.as_str()), clean(expected));
     }
 
+    #[test]
+    fn report_targets_test() {
+        let data = StatusReportData {
+            activity: None,
+            paused: None,
+            project: None,
+            current_month: Duration::hours(10),
+            range: None,
+            current_week: Duration::hours(5),
+            today: Duration::minutes(30),
+            targets: Some(StatusTargets {
+                today: Duration::zero(),
+                current_week: Duration::hours(8),
+                current_month: Duration::hours(16),
+                balance: Some(Duration::hours(-6)),
+            }),
+        };
+        let expected = "\u{1b}[2m
+ =======<>[3m Status for <>[1mALL<>[3m projects <>[2m ======= 
+<>[2;3m
+  NOW: <>[1m NO Activity
+
+<>[3m <>[2;3m Today......................... <>[1m30m<>[2m / <>[2m0m<>[3m
+<>[3m <>[2;3m Current week.................. <>[1m5h 00m<>[2m / <>[2m8h 00m<>[3m
+<>[3m <>[2;3m Current month................. <>[1m10h 00m<>[2m / <>[2m16h 00m<>[3m
+<>[3m <>[2;3m Balance....................... <>[1;31m-6h 00m<>[3m
+\u{1b}[0m";
+
+        let res = data.to_string();
+
+        assert_eq!(clean(res.as_str()), clean(expected));
+    }
+
     #[test]
     fn report_project_test() {
         let data = StatusReportData {
             activity: None,
+            paused: None,
             project: Some("project"),
             current_month: Duration::hours(10),
+            range: None,
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            targets: None,
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for project: <>[1mproject<>[2m ======= 
@@ -180,13 +478,22 @@ mod tests {
             end: None,
             project: "project".to_string(),
             description: "olia".to_string(),
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
+            start_offset: None,
+            end_offset: None,
         };
         let data = StatusReportData {
             activity: Some(&act),
+            paused: None,
             project: Some("project"),
             current_month: Duration::hours(10),
+            range: None,
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            targets: None,
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for project: <>[1mproject<>[2m ======= 
@@ -211,13 +518,22 @@ mod tests {
             end: None,
             project: "project".to_string(),
             description: "olia".to_string(),
+            tags: Vec::new(),
+            paused: false,
+            resumed: false,
+            notes: None,
+            start_offset: None,
+            end_offset: None,
         };
         let data = StatusReportData {
             activity: Some(&act),
+            paused: None,
             project: None,
             current_month: Duration::hours(10),
+            range: None,
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            targets: None,
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for <>[1mALL<>[3m projects <>[2m ======= 
@@ -234,6 +550,121 @@ mod tests {
         assert_eq!(clean(res.as_str()), clean(expected));
     }
 
+    #[test]
+    fn report_paused_test() {
+        let now = Local::now().naive_local();
+        let act = activity::Activity {
+            start: now - Duration::minutes(10),
+            end: Some(now),
+            project: "project".to_string(),
+            description: "olia".to_string(),
+            tags: Vec::new(),
+            paused: true,
+            resumed: false,
+            notes: None,
+            start_offset: None,
+            end_offset: None,
+        };
+        let data = StatusReportData {
+            activity: None,
+            paused: Some(&act),
+            project: None,
+            current_month: Duration::hours(10),
+            range: None,
+            current_week: Duration::hours(5),
+            today: Duration::minutes(30),
+            targets: None,
+        };
+        let expected = "\u{1b}[2m
+ =======<>[3m Status for <>[1mALL<>[3m projects <>[2m ======= 
+<>[2;3m
+  PAUSED: <>[1;33molia<>[2;3m on <>[3mproject<>[2m
+
+<>[3m <>[2;3m Today......................... <>[1m30m<>[3m
+<>[3m <>[2;3m Current week.................. <>[1m5h 00m<>[3m
+<>[3m <>[2;3m Current month................. <>[1m10h 00m<>[3m
+\u{1b}[0m";
+
+        let res = data.to_string();
+
+        assert_eq!(clean(res.as_str()), clean(expected));
+    }
+
+    #[test]
+    fn status_formats() {
+        let now = Local::now().naive_local();
+        let act = activity::Activity {
+            start: now - Duration::minutes(10),
+            end: None,
+            project: "project".to_string(),
+            description: "olia".to_string(),
+            tags: vec!["tag".to_string()],
+            paused: false,
+            resumed: false,
+            notes: None,
+            start_offset: None,
+            end_offset: None,
+        };
+        let mut data = StatusReportData {
+            activity: Some(&act),
+            paused: None,
+            project: None,
+            current_month: Duration::hours(10),
+            range: None,
+            current_week: Duration::hours(5),
+            today: Duration::minutes(30),
+            targets: None,
+        };
+
+        assert_eq!(to_line(&data), "olia on project 10m | today 30m");
+
+        let json = to_json(&data);
+        assert_eq!(json["state"], "active");
+        assert_eq!(json["activity"]["description"], "olia");
+        assert_eq!(json["activity"]["tags"][0], "tag");
+        assert_eq!(json["activity"]["duration"], 600);
+        assert_eq!(json["today"], 1800);
+        assert!(json["paused"].is_null());
+        assert!(json["targets"].is_null());
+        assert!(json["range"].is_null());
+
+        data.range = Some(Duration::hours(40));
+        assert_eq!(to_json(&data)["range"], 144_000);
+        data.range = None;
+
+        let waybar = to_waybar(&data);
+        assert_eq!(waybar["class"], "active");
+        assert_eq!(waybar["text"], "olia on project 10m | today 30m");
+        assert_eq!(
+            waybar["tooltip"],
+            "Today: 30m\nCurrent week: 5h 00m\nCurrent month: 10h 00m"
+        );
+
+        let i3bar = to_i3bar(&data);
+        assert_eq!(i3bar["short_text"], "olia on project 10m");
+        assert_eq!(i3bar["color"], "#00ff00");
+
+        data.activity = None;
+        data.targets = Some(StatusTargets {
+            today: Duration::hours(8),
+            current_week: Duration::hours(8),
+            current_month: Duration::hours(16),
+            balance: Some(Duration::hours(-6)),
+        });
+
+        assert_eq!(to_line(&data), "no activity | today 30m / 8h 00m");
+        assert_eq!(to_waybar(&data)["alt"], "idle");
+
+        assert_eq!(to_json(&data)["targets"]["balance"], -6 * 3600);
+        assert!(to_waybar(&data)["tooltip"]
+            .as_str()
+            .unwrap()
+            .ends_with("\nBalance: -6h 00m"));
+        assert!(to_i3bar(&data).get("color").is_none());
+
+        assert_eq!(escape_markup("<1m & more"), "&lt;1m &amp; more");
+    }
+
     fn clean(a: &str) -> String {
         let st_f = "\u{1b}[0m\u{1b}";
 
@@ -7,6 +7,7 @@ use nu_ansi_term::Style;
 use textwrap;
 
 use crate::conf;
+use crate::view::format_util;
 
 pub enum Wrap {
     Wrap,
@@ -196,7 +197,12 @@ impl fmt::Display for Table {
 
         let labels: Vec<&String> = self.columns.iter().map(|c| &c.label).collect();
 
-        write_cells(f, &labels, &column_width, Some(Style::new().underline()))?;
+        write_cells(
+            f,
+            &labels,
+            &column_width,
+            Some(format_util::style(Style::new().underline())),
+        )?;
         writeln!(f)?;
 
         for row in &self.rows {
@@ -216,7 +222,11 @@ fn write_group(f: &mut fmt::Formatter<'_>, group: &Group, column_width: &[usize]
     let title = group.title.as_ref().unwrap_or(&empty_string);
 
     writeln!(f)?;
-    writeln!(f, "{}", Style::new().bold().paint(title))?;
+    writeln!(
+        f,
+        "{}",
+        format_util::style(Style::new().bold()).paint(title)
+    )?;
 
     for row in &group.rows {
         write_row(f, row, column_width)?;
@@ -277,6 +287,7 @@ fn write_with_width_and_style(
     width: &usize,
     opt_style: Option<Style>,
 ) -> fmt::Result {
+    let opt_style = opt_style.map(format_util::style);
     let style_prefix = opt_style.map_or(String::new(), |style| style.prefix().to_string());
     let style_suffix = opt_style.map_or(String::new(), |style| style.suffix().to_string());
 
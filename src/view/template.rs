@@ -0,0 +1,368 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, NaiveDateTime};
+use std::fmt::Write;
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::processor::StatusReportData;
+use crate::view::format_util;
+
+// the kinds of values a field may have. They determine which format specifiers are allowed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Text,
+    DateTime,
+    Duration,
+}
+
+// the value of a field. Missing values are rendered as empty text
+pub enum FieldValue {
+    Text(String),
+    DateTime(Option<NaiveDateTime>),
+    Duration(Option<Duration>),
+}
+
+// anything that provides the values for the fields of a template
+pub trait TemplateFields {
+    fn get_field(&self, name: &str) -> FieldValue;
+}
+
+// the fields of an activity in `list` and `current`
+pub static ACTIVITY_FIELDS: [(&str, FieldKind); 8] = [
+    ("index", FieldKind::Text),
+    ("project", FieldKind::Text),
+    ("description", FieldKind::Text),
+    ("tags", FieldKind::Text),
+    ("notes", FieldKind::Text),
+    ("start", FieldKind::DateTime),
+    ("end", FieldKind::DateTime),
+    ("duration", FieldKind::Duration),
+];
+
+// the fields of `status`. The activity fields refer to the running or else the paused activity
+pub static STATUS_FIELDS: [(&str, FieldKind); 16] = [
+    ("state", FieldKind::Text),
+    ("project", FieldKind::Text),
+    ("description", FieldKind::Text),
+    ("tags", FieldKind::Text),
+    ("notes", FieldKind::Text),
+    ("start", FieldKind::DateTime),
+    ("end", FieldKind::DateTime),
+    ("duration", FieldKind::Duration),
+    ("today", FieldKind::Duration),
+    ("current_week", FieldKind::Duration),
+    ("current_month", FieldKind::Duration),
+    ("range", FieldKind::Duration),
+    ("today_target", FieldKind::Duration),
+    ("current_week_target", FieldKind::Duration),
+    ("current_month_target", FieldKind::Duration),
+    ("balance", FieldKind::Duration),
+];
+
+// the format specifiers of durations. Without specifier durations are shown like `1h 30m`
+static DURATION_SPECIFIERS: [&str; 4] = ["hours", "minutes", "seconds", "clock"];
+
+#[derive(Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Field { name: String, spec: Option<String> },
+}
+
+// a template like `{project}: {description} ({duration:hours})`
+//
+// fields are written in braces and may have a format specifier after a colon: a strftime
+// format for dates like `{start:%H:%M}` or one of `hours`, `minutes`, `seconds` and `clock`
+// for durations. Literal braces are written as `{{` and `}}`.
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    // parses a template and checks that it only contains the given fields
+    pub fn parse(template: &str, fields: &[(&str, FieldKind)]) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => bail!("Missing }} in template \"{template}\""),
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(parse_field(&field, fields)?);
+                }
+                '}' => bail!("Unexpected }} in template \"{template}\". Use }}}} for a literal }}"),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Template { parts })
+    }
+
+    #[must_use]
+    pub fn render(&self, values: &dyn TemplateFields) -> String {
+        let mut result = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => result.push_str(text),
+                Part::Field { name, spec } => {
+                    result.push_str(&format_value(values.get_field(name), spec.as_deref()));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+fn parse_field(field: &str, fields: &[(&str, FieldKind)]) -> Result<Part> {
+    let (name, spec) = match field.split_once(':') {
+        Some((name, spec)) => (name.trim(), Some(spec)),
+        None => (field.trim(), None),
+    };
+
+    let Some((_, kind)) = fields.iter().find(|(n, _)| *n == name) else {
+        bail!(
+            "Unknown field {{{name}}} in template. Use one of: {}",
+            fields
+                .iter()
+                .map(|(n, _)| *n)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        );
+    };
+
+    if let Some(spec) = spec {
+        match kind {
+            FieldKind::Text => bail!("The field {{{name}}} does not support a format"),
+            FieldKind::DateTime => {
+                // the times of activities have no time zone, so formats with an offset or a
+                // time zone name fail just like invalid formats
+                let sample = DateTime::UNIX_EPOCH.naive_utc();
+                if write!(String::new(), "{}", sample.format(spec)).is_err() {
+                    bail!("\"{spec}\" is not a valid date format for the field {{{name}}}. Time zones and offsets are not supported");
+                }
+            }
+            FieldKind::Duration => {
+                if !DURATION_SPECIFIERS.contains(&spec) {
+                    bail!(
+                        "\"{spec}\" is not a valid duration format for the field {{{name}}}. Use one of: {}",
+                        DURATION_SPECIFIERS.join(", ")
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(Part::Field {
+        name: name.to_string(),
+        spec: spec.map(str::to_string),
+    })
+}
+
+fn format_value(value: FieldValue, spec: Option<&str>) -> String {
+    match value {
+        FieldValue::Text(text) => text,
+        FieldValue::DateTime(datetime) => datetime.map_or_else(String::new, |datetime| {
+            datetime
+                .format(spec.unwrap_or(conf::FORMAT_DATETIME))
+                .to_string()
+        }),
+        FieldValue::Duration(duration) => {
+            duration.map_or_else(String::new, |duration| format_duration(duration, spec))
+        }
+    }
+}
+
+// formats a duration that may be negative like a balance
+fn format_duration(duration: Duration, spec: Option<&str>) -> String {
+    match spec {
+        Some("hours") => format!("{:.2}", duration.num_seconds() as f64 / 3600.0),
+        Some("minutes") => duration.num_minutes().to_string(),
+        Some("seconds") => duration.num_seconds().to_string(),
+        Some("clock") => {
+            let sign = if duration < Duration::zero() { "-" } else { "" };
+            let minutes = duration.num_minutes().abs();
+            format!("{sign}{}:{:02}", minutes / 60, minutes % 60)
+        }
+        _ if duration < Duration::zero() => {
+            format!("-{}", format_util::format_exact_duration(&-duration))
+        }
+        _ => format_util::format_exact_duration(&duration),
+    }
+}
+
+fn get_activity_field(activity: Option<&activity::Activity>, name: &str) -> FieldValue {
+    match name {
+        "project" => FieldValue::Text(activity.map_or_else(String::new, |a| a.project.clone())),
+        "description" => {
+            FieldValue::Text(activity.map_or_else(String::new, |a| a.description.clone()))
+        }
+        "tags" => {
+            FieldValue::Text(activity.map_or_else(String::new, |a| activity::format_tags(&a.tags)))
+        }
+        "notes" => FieldValue::Text(activity.and_then(|a| a.notes.clone()).unwrap_or_default()),
+        "start" => FieldValue::DateTime(activity.map(|a| a.start)),
+        "end" => FieldValue::DateTime(activity.and_then(|a| a.end)),
+        "duration" => FieldValue::Duration(activity.map(activity::Activity::get_duration)),
+        _ => FieldValue::Text(String::new()),
+    }
+}
+
+// an activity together with its index
+impl TemplateFields for (Option<usize>, &activity::Activity) {
+    fn get_field(&self, name: &str) -> FieldValue {
+        match name {
+            "index" => FieldValue::Text(self.0.map_or_else(String::new, |i| i.to_string())),
+            _ => get_activity_field(Some(self.1), name),
+        }
+    }
+}
+
+impl TemplateFields for StatusReportData<'_> {
+    fn get_field(&self, name: &str) -> FieldValue {
+        let targets = self.targets.as_ref();
+
+        match name {
+            "state" => FieldValue::Text(self.get_state().to_string()),
+            "today" => FieldValue::Duration(Some(self.today)),
+            "current_week" => FieldValue::Duration(Some(self.current_week)),
+            "current_month" => FieldValue::Duration(Some(self.current_month)),
+            "range" => FieldValue::Duration(self.range),
+            "today_target" => FieldValue::Duration(targets.map(|t| t.today)),
+            "current_week_target" => FieldValue::Duration(targets.map(|t| t.current_week)),
+            "current_month_target" => FieldValue::Duration(targets.map(|t| t.current_month)),
+            "balance" => FieldValue::Duration(targets.and_then(|t| t.balance)),
+            _ => get_activity_field(self.activity.or(self.paused), name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::data::processor::StatusTargets;
+
+    fn data() -> activity::Activity {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let mut a =
+            activity::Activity::start("project".to_string(), "olia".to_string(), Some(start));
+        a.end = Some(start + Duration::minutes(90));
+        a.tags = vec!["meeting".to_string(), "call".to_string()];
+        a.set_notes("see ticket #42");
+        a
+    }
+
+    #[test]
+    fn render_activity() {
+        let a = data();
+        let render = |template: &str| {
+            Template::parse(template, &ACTIVITY_FIELDS)
+                .unwrap()
+                .render(&(Some(3), &a))
+        };
+
+        assert_eq!(
+            render("{index}: {project}: {description} ({duration})"),
+            "3: project: olia (1h 30m)"
+        );
+        assert_eq!(
+            render("{start:%H:%M}-{end:%H:%M} {tags}"),
+            "09:00-10:30 +meeting +call"
+        );
+        assert_eq!(
+            render("{duration:hours} {duration:minutes} {duration:seconds} {duration:clock}"),
+            "1.50 90 5400 1:30"
+        );
+        assert_eq!(render("{description}: {notes}"), "olia: see ticket #42");
+        assert_eq!(render("{{ {project} }}"), "{ project }");
+        assert_eq!(render(""), "");
+    }
+
+    #[test]
+    fn render_running_activity() {
+        let mut a = data();
+        a.end = None;
+
+        let template = Template::parse("{start:%F}|{end}|", &ACTIVITY_FIELDS).unwrap();
+
+        assert_eq!(template.render(&(None, &a)), "2024-03-01||");
+    }
+
+    #[test]
+    fn render_status() {
+        let a = data();
+        let mut status = StatusReportData {
+            activity: None,
+            paused: Some(&a),
+            project: None,
+            today: Duration::minutes(30),
+            current_week: Duration::hours(5),
+            current_month: Duration::hours(10),
+            range: None,
+            targets: None,
+        };
+        let template = Template::parse(
+            "{state} {description} {today}/{today_target} {balance:clock}",
+            &STATUS_FIELDS,
+        )
+        .unwrap();
+
+        assert_eq!(template.render(&status), "paused olia 30m/ ");
+
+        status.paused = None;
+        status.targets = Some(StatusTargets {
+            today: Duration::zero(),
+            current_week: Duration::hours(8),
+            current_month: Duration::hours(16),
+            balance: Some(Duration::minutes(-90)),
+        });
+
+        assert_eq!(template.render(&status), "idle  30m/0m -1:30");
+    }
+
+    #[test]
+    fn parse_invalid_templates() {
+        assert!(Template::parse("{project", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("project}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{unknown}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{today}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{project:%H}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{start:%Q}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{start:%z} {project}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{end:%Z}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{start:%:z}", &STATUS_FIELDS).is_err());
+        assert!(Template::parse("{start:%+}", &STATUS_FIELDS).is_err());
+        assert!(Template::parse("{duration:days}", &ACTIVITY_FIELDS).is_err());
+        assert!(Template::parse("{index}", &STATUS_FIELDS).is_err());
+    }
+}